@@ -0,0 +1,28 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Set this to also copy the generated header into `include/`, which is
+/// checked in for C users who don't build with cargo.
+const WRITE_HEADER_VAR: &str = "AOC_FFI_WRITE_HEADER";
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed={}", WRITE_HEADER_VAR);
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("failed to read cbindgen.toml");
+    let header = out_dir.join("aoc_ffi.h");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("failed to generate C bindings")
+        .write_to_file(&header);
+    if env::var_os(WRITE_HEADER_VAR).is_some() {
+        fs::copy(&header, crate_dir.join("include/aoc_ffi.h"))
+            .expect("failed to copy the header into include/");
+    }
+}
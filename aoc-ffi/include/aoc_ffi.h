@@ -0,0 +1,83 @@
+#ifndef AOC_FFI_H
+#define AOC_FFI_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The answer was written to the output buffer.
+ */
+#define AOC_OK 0
+
+/**
+ * `input_ptr`, `out_buf` or `out_buf->data` was null.
+ */
+#define AOC_ERR_NULL_POINTER -1
+
+/**
+ * The input wasn't valid UTF-8.
+ */
+#define AOC_ERR_INVALID_UTF8 -2
+
+/**
+ * No solution is registered for the requested year and day.
+ */
+#define AOC_ERR_UNKNOWN_PUZZLE -3
+
+/**
+ * `part` was neither 1 nor 2.
+ */
+#define AOC_ERR_INVALID_PART -4
+
+/**
+ * The solver couldn't parse the input.
+ *
+ * The output buffer holds the solver's message, truncated to fit. The
+ * solvers report bad input by panicking, so any other panic in a solver
+ * is reported this way too, rather than unwinding into the caller.
+ */
+#define AOC_ERR_PARSE -5
+
+/**
+ * The answer didn't fit in the output buffer.
+ *
+ * `out_buf->len` is set to the length the answer needs, not counting
+ * the trailing NUL, and nothing is written to `out_buf->data`.
+ */
+#define AOC_ERR_BUFFER_TOO_SMALL -6
+
+/**
+ * A caller-owned buffer for the answer.
+ *
+ * On success `data` holds the NUL-terminated answer and `len` its length
+ * without the NUL.
+ */
+typedef struct AocBuffer {
+  char *data;
+  size_t capacity;
+  size_t len;
+} AocBuffer;
+
+/**
+ * Solve one part of a puzzle.
+ *
+ * `input_ptr` points to `len` bytes of UTF-8 puzzle input, exactly as
+ * downloaded. Returns `AOC_OK` or one of the `AOC_ERR_*` codes.
+ *
+ * # Safety
+ *
+ * `input_ptr` must be valid for reads of `len` bytes, and `out_buf` must
+ * point to an `AocBuffer` whose `data` is valid for writes of `capacity`
+ * bytes.
+ */
+int32_t aoc_solve(uint16_t year,
+                  uint8_t day,
+                  uint8_t part,
+                  const uint8_t *input_ptr,
+                  size_t len,
+                  struct AocBuffer *out_buf);
+
+#endif  /* AOC_FFI_H */
@@ -0,0 +1,186 @@
+//! A C ABI over the solver registry.
+//!
+//! The header for this library is generated by `build.rs` into
+//! `OUT_DIR`. A copy is checked in at `include/aoc_ffi.h`; refresh it by
+//! building with `AOC_FFI_WRITE_HEADER=1` set.
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+use std::str;
+
+use aoc_registry::{Error, Part};
+
+/// The answer was written to the output buffer.
+pub const AOC_OK: i32 = 0;
+/// `input_ptr`, `out_buf` or `out_buf->data` was null.
+pub const AOC_ERR_NULL_POINTER: i32 = -1;
+/// The input wasn't valid UTF-8.
+pub const AOC_ERR_INVALID_UTF8: i32 = -2;
+/// No solution is registered for the requested year and day.
+pub const AOC_ERR_UNKNOWN_PUZZLE: i32 = -3;
+/// `part` was neither 1 nor 2.
+pub const AOC_ERR_INVALID_PART: i32 = -4;
+/// The solver couldn't parse the input.
+///
+/// The output buffer holds the solver's message, truncated to fit. The
+/// solvers report bad input by panicking, so any other panic in a solver
+/// is reported this way too, rather than unwinding into the caller.
+pub const AOC_ERR_PARSE: i32 = -5;
+/// The answer didn't fit in the output buffer.
+///
+/// `out_buf->len` is set to the length the answer needs, not counting
+/// the trailing NUL, and nothing is written to `out_buf->data`.
+pub const AOC_ERR_BUFFER_TOO_SMALL: i32 = -6;
+
+/// A caller-owned buffer for the answer.
+///
+/// On success `data` holds the NUL-terminated answer and `len` its length
+/// without the NUL.
+#[repr(C)]
+pub struct AocBuffer {
+    pub data: *mut c_char,
+    pub capacity: usize,
+    pub len: usize,
+}
+
+/// Copy as much of `s` as fits into `buf`, NUL-terminated, without
+/// splitting a character.
+unsafe fn write_truncated(buf: &mut AocBuffer, s: &str) {
+    if buf.capacity == 0 {
+        buf.len = 0;
+        return;
+    }
+    let mut n = s.len().min(buf.capacity - 1);
+    while !s.is_char_boundary(n) {
+        n -= 1;
+    }
+    ptr::copy_nonoverlapping(s.as_ptr(), buf.data as *mut u8, n);
+    *buf.data.add(n) = 0;
+    buf.len = n;
+}
+
+/// Solve one part of a puzzle.
+///
+/// `input_ptr` points to `len` bytes of UTF-8 puzzle input, exactly as
+/// downloaded. Returns `AOC_OK` or one of the `AOC_ERR_*` codes.
+///
+/// # Safety
+///
+/// `input_ptr` must be valid for reads of `len` bytes, and `out_buf` must
+/// point to an `AocBuffer` whose `data` is valid for writes of `capacity`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(
+    year: u16,
+    day: u8,
+    part: u8,
+    input_ptr: *const u8,
+    len: usize,
+    out_buf: *mut AocBuffer,
+) -> i32 {
+    if input_ptr.is_null() || out_buf.is_null() || (*out_buf).data.is_null() {
+        return AOC_ERR_NULL_POINTER;
+    }
+    let out_buf = &mut *out_buf;
+    let input = match str::from_utf8(slice::from_raw_parts(input_ptr, len)) {
+        Ok(input) => input,
+        Err(_) => return AOC_ERR_INVALID_UTF8,
+    };
+    let part = match Part::from_number(part) {
+        Some(part) => part,
+        None => return AOC_ERR_INVALID_PART,
+    };
+    match aoc_registry::solve(year, day, part, input) {
+        Ok(answer) => {
+            if answer.len() >= out_buf.capacity {
+                out_buf.len = answer.len();
+                return AOC_ERR_BUFFER_TOO_SMALL;
+            }
+            write_truncated(out_buf, &answer);
+            AOC_OK
+        }
+        Err(Error::UnknownPuzzle { .. }) => AOC_ERR_UNKNOWN_PUZZLE,
+        Err(Error::Input(msg)) => {
+            write_truncated(out_buf, &msg);
+            AOC_ERR_PARSE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::os::raw::c_char;
+
+    use super::{
+        aoc_solve, write_truncated, AocBuffer, AOC_ERR_BUFFER_TOO_SMALL, AOC_ERR_INVALID_PART,
+        AOC_ERR_INVALID_UTF8, AOC_ERR_PARSE, AOC_ERR_UNKNOWN_PUZZLE, AOC_OK,
+    };
+
+    fn solve(year: u16, day: u8, part: u8, input: &[u8], capacity: usize) -> (i32, String) {
+        let mut data = vec![0u8; capacity.max(1)];
+        let mut buf = AocBuffer {
+            data: data.as_mut_ptr() as *mut c_char,
+            capacity,
+            len: 0,
+        };
+        let code = unsafe { aoc_solve(year, day, part, input.as_ptr(), input.len(), &mut buf) };
+        let written = if code == AOC_ERR_BUFFER_TOO_SMALL {
+            buf.len.to_string()
+        } else {
+            String::from_utf8(data[..buf.len].to_vec()).unwrap()
+        };
+        (code, written)
+    }
+
+    #[test]
+    fn test_aoc_solve() {
+        assert_eq!(
+            solve(2018, 1, 1, b"+1\n-2\n+3\n", 16),
+            (AOC_OK, "2".to_string())
+        );
+        assert_eq!(
+            solve(
+                2018,
+                7,
+                1,
+                b"Step C must be finished before step A can begin.",
+                16
+            ),
+            (AOC_OK, "CA".to_string())
+        );
+    }
+
+    #[test]
+    fn test_aoc_solve_errors() {
+        assert_eq!(solve(2018, 30, 1, b"", 16).0, AOC_ERR_UNKNOWN_PUZZLE);
+        assert_eq!(solve(2018, 1, 3, b"+1", 16).0, AOC_ERR_INVALID_PART);
+        assert_eq!(solve(2018, 1, 1, b"+1\n\xff", 16).0, AOC_ERR_INVALID_UTF8);
+        assert_eq!(solve(2018, 1, 1, b"+1\nx", 16).0, AOC_ERR_PARSE);
+        assert_eq!(
+            solve(2018, 1, 1, b"+1000", 4),
+            (AOC_ERR_BUFFER_TOO_SMALL, "4".to_string())
+        );
+    }
+
+    #[test]
+    fn test_write_truncated() {
+        let truncated = |s: &str, capacity: usize| {
+            let mut data = vec![0xffu8; capacity.max(1)];
+            let mut buf = AocBuffer {
+                data: data.as_mut_ptr() as *mut c_char,
+                capacity,
+                len: 0,
+            };
+            unsafe { write_truncated(&mut buf, s) };
+            assert!(capacity == 0 || data[buf.len] == 0);
+            String::from_utf8(data[..buf.len].to_vec()).unwrap()
+        };
+        assert_eq!(truncated("abc", 8), "abc");
+        assert_eq!(truncated("abc", 3), "ab");
+        assert_eq!(truncated("abc", 0), "");
+        // "é" is two bytes, so it's dropped rather than split.
+        assert_eq!(truncated("aé", 3), "a");
+        assert_eq!(truncated("aé", 4), "aé");
+    }
+}
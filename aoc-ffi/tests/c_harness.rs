@@ -0,0 +1,45 @@
+//! Build `harness.c` against the generated header and the shared library
+//! produced by this crate, then run it.
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn test_c_harness() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Integration tests live alongside the crate's own artifacts, so the
+    // cdylib is in the same directory as this test binary.
+    let lib_dir = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let harness = out_dir.join("aoc_ffi_harness");
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .arg(crate_dir.join("tests/harness.c"))
+        .arg("-I")
+        .arg(env!("OUT_DIR"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-laoc_ffi")
+        .arg("-o")
+        .arg(&harness)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "failed to compile the C harness");
+
+    // Cargo puts `target/debug` on the library path, which can hold an
+    // older build of the cdylib that would otherwise win over the rpath.
+    let output = Command::new(&harness)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .expect("failed to run the C harness");
+    assert!(
+        output.status.success(),
+        "C harness failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("2018 day 1 part 2: 10"));
+}
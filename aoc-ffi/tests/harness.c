@@ -0,0 +1,40 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "aoc_ffi.h"
+
+static int check(uint16_t year, uint8_t day, uint8_t part, const char *input,
+                 int32_t expected_code, const char *expected_answer) {
+    char data[64];
+    AocBuffer buf = {data, sizeof(data), 0};
+    int32_t code = aoc_solve(year, day, part, (const uint8_t *)input,
+                             strlen(input), &buf);
+    if (code != expected_code) {
+        fprintf(stderr, "%u day %u part %u: expected code %d, got %d\n", year,
+                day, part, expected_code, code);
+        return 1;
+    }
+    if (expected_answer != NULL && strcmp(data, expected_answer) != 0) {
+        fprintf(stderr, "%u day %u part %u: expected '%s', got '%s'\n", year,
+                day, part, expected_answer, data);
+        return 1;
+    }
+    if (code == AOC_OK) {
+        printf("%u day %u part %u: %s\n", year, day, part, data);
+    }
+    return 0;
+}
+
+int main(void) {
+    int failures = 0;
+    failures += check(2018, 1, 1, "+1\n+1\n-2\n", AOC_OK, "0");
+    failures += check(2018, 1, 2, "+3\n+3\n+4\n-2\n-4\n", AOC_OK, "10");
+    failures += check(2018, 5, 1, "dabAcCaCBAcCcaDA\n", AOC_OK, "10");
+    failures += check(2018, 8, 2, "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2", AOC_OK,
+                      "66");
+    failures += check(2018, 1, 1, "+1\nbogus\n", AOC_ERR_PARSE, NULL);
+    failures += check(2018, 1, 2, "+1\n", AOC_ERR_PARSE, NULL);
+    failures += check(2018, 1, 7, "+1\n", AOC_ERR_INVALID_PART, NULL);
+    failures += check(2017, 1, 1, "+1\n", AOC_ERR_UNKNOWN_PUZZLE, NULL);
+    return failures;
+}
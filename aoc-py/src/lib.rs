@@ -0,0 +1,253 @@
+//! Python bindings for the solvers.
+//!
+//! Build a wheel with `maturin build` from this directory, then:
+//!
+//! ```python
+//! import aoc
+//!
+//! day = aoc.Day07(workers=5, base_duration=60)
+//! steps = day.parse(open("day7.txt").read())
+//! print(day.part1(steps), day.part2(steps))
+//! ```
+//!
+//! Each day's `parse` turns the raw puzzle text into the form its solvers
+//! take: a list of lines, a trimmed string, or (for day 11) the serial
+//! number. The solvers themselves parse each line lazily, so a malformed
+//! line raises `aoc.ParseError` from `part1` or `part2`.
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use aoc_registry::{catch_input_errors, Error};
+
+create_exception!(
+    aoc,
+    ParseError,
+    PyValueError,
+    "The puzzle input couldn't be parsed."
+);
+
+fn solve<T>(f: impl FnOnce() -> T + std::panic::UnwindSafe) -> PyResult<T> {
+    catch_input_errors(f).map_err(|e| match e {
+        Error::Input(msg) => ParseError::new_err(msg),
+        other => ParseError::new_err(other.to_string()),
+    })
+}
+
+fn lines(input: &str) -> Vec<String> {
+    input.lines().map(String::from).collect()
+}
+
+fn as_strs(lines: &[String]) -> Vec<&str> {
+    lines.iter().map(String::as_str).collect()
+}
+
+/// Define a class for a day which takes its input as lines and has no
+/// parameters.
+macro_rules! line_day {
+    ($name:ident, $part1:path, $part2:path) => {
+        #[pyclass(module = "aoc")]
+        struct $name;
+
+        #[pymethods]
+        impl $name {
+            #[new]
+            fn new() -> Self {
+                $name
+            }
+
+            fn parse(&self, input: &str) -> Vec<String> {
+                lines(input)
+            }
+
+            fn part1(&self, input: Vec<String>) -> PyResult<String> {
+                solve(|| $part1(&as_strs(&input)).to_string())
+            }
+
+            fn part2(&self, input: Vec<String>) -> PyResult<String> {
+                solve(|| $part2(&as_strs(&input)).to_string())
+            }
+        }
+    };
+}
+
+#[pyclass(module = "aoc")]
+struct Day01;
+
+#[pymethods]
+impl Day01 {
+    #[new]
+    fn new() -> Self {
+        Day01
+    }
+
+    fn parse(&self, input: &str) -> Vec<String> {
+        lines(input)
+    }
+
+    fn part1(&self, input: Vec<String>) -> PyResult<String> {
+        solve(|| day01::part1(&as_strs(&input)).to_string())
+    }
+
+    /// Raises `ValueError` if no frequency is ever repeated.
+    fn part2(&self, input: Vec<String>) -> PyResult<String> {
+        solve(|| day01::part2_analytic(&as_strs(&input)))?
+            .map(|frequency| frequency.to_string())
+            .ok_or_else(|| PyValueError::new_err("no frequency is ever repeated"))
+    }
+}
+
+line_day!(Day02, day02::part1, day02::part2);
+line_day!(Day03, day03::part1, day03::part2);
+line_day!(Day04, day04::part1, day04::part2);
+
+#[pyclass(module = "aoc")]
+struct Day05;
+
+#[pymethods]
+impl Day05 {
+    #[new]
+    fn new() -> Self {
+        Day05
+    }
+
+    fn parse(&self, input: &str) -> String {
+        input.trim().to_string()
+    }
+
+    fn part1(&self, input: &str) -> PyResult<usize> {
+        solve(|| day05::part1(input))
+    }
+
+    fn part2(&self, input: &str) -> PyResult<usize> {
+        solve(|| day05::part2(input))
+    }
+}
+
+#[pyclass(module = "aoc")]
+struct Day06 {
+    #[pyo3(get)]
+    max_distance: i32,
+}
+
+#[pymethods]
+impl Day06 {
+    #[new]
+    #[pyo3(signature = (max_distance = 10000))]
+    fn new(max_distance: i32) -> Self {
+        Day06 { max_distance }
+    }
+
+    fn parse(&self, input: &str) -> Vec<String> {
+        lines(input)
+    }
+
+    fn part1(&self, input: Vec<String>) -> PyResult<i32> {
+        solve(|| day06::part1(&as_strs(&input)))
+    }
+
+    fn part2(&self, input: Vec<String>) -> PyResult<i32> {
+        solve(|| day06::part2(&as_strs(&input), self.max_distance))
+    }
+}
+
+#[pyclass(module = "aoc")]
+struct Day07 {
+    #[pyo3(get)]
+    workers: usize,
+    #[pyo3(get)]
+    base_duration: usize,
+}
+
+#[pymethods]
+impl Day07 {
+    #[new]
+    #[pyo3(signature = (workers = 5, base_duration = 60))]
+    fn new(workers: usize, base_duration: usize) -> Self {
+        Day07 {
+            workers,
+            base_duration,
+        }
+    }
+
+    fn parse(&self, input: &str) -> Vec<String> {
+        lines(input)
+    }
+
+    fn part1(&self, input: Vec<String>) -> PyResult<String> {
+        solve(|| day07::part1(&as_strs(&input)))
+    }
+
+    fn part2(&self, input: Vec<String>) -> PyResult<String> {
+        solve(|| day07::part2(&as_strs(&input), self.workers, self.base_duration))
+    }
+}
+
+#[pyclass(module = "aoc")]
+struct Day08;
+
+#[pymethods]
+impl Day08 {
+    #[new]
+    fn new() -> Self {
+        Day08
+    }
+
+    fn parse(&self, input: &str) -> String {
+        input.trim().to_string()
+    }
+
+    fn part1(&self, input: &str) -> PyResult<usize> {
+        solve(|| day08::part1(input))
+    }
+
+    fn part2(&self, input: &str) -> PyResult<usize> {
+        solve(|| day08::part2(input))
+    }
+}
+
+#[pyclass(module = "aoc")]
+struct Day11 {
+    #[pyo3(get)]
+    width: usize,
+}
+
+#[pymethods]
+impl Day11 {
+    #[new]
+    #[pyo3(signature = (width = 300))]
+    fn new(width: usize) -> Self {
+        Day11 { width }
+    }
+
+    /// The input is just the grid serial number.
+    fn parse(&self, input: &str) -> PyResult<usize> {
+        input
+            .trim()
+            .parse()
+            .map_err(|_| ParseError::new_err("serial number must be a positive integer"))
+    }
+
+    fn part1(&self, serial: usize) -> PyResult<(usize, usize)> {
+        solve(|| day11::part1(serial, self.width, 3))
+    }
+
+    fn part2(&self, serial: usize) -> PyResult<(usize, usize, usize)> {
+        solve(|| day11::part2(serial, self.width))
+    }
+}
+
+#[pymodule]
+pub fn aoc(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("ParseError", m.py().get_type::<ParseError>())?;
+    m.add_class::<Day01>()?;
+    m.add_class::<Day02>()?;
+    m.add_class::<Day03>()?;
+    m.add_class::<Day04>()?;
+    m.add_class::<Day05>()?;
+    m.add_class::<Day06>()?;
+    m.add_class::<Day07>()?;
+    m.add_class::<Day08>()?;
+    m.add_class::<Day11>()?;
+    Ok(())
+}
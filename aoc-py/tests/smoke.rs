@@ -0,0 +1,78 @@
+//! Import the module into an embedded interpreter and drive it from
+//! Python, without needing pytest or an installed wheel.
+use pyo3::ffi::c_str;
+use pyo3::prelude::*;
+
+use aoc_py::aoc;
+
+#[test]
+fn test_python_smoke() {
+    pyo3::append_to_inittab!(aoc);
+    Python::initialize();
+    Python::attach(|py| {
+        py.run(
+            c_str!(
+                r#"
+import aoc
+
+day01 = aoc.Day01()
+deltas = day01.parse("+3\n+3\n+4\n-2\n-4\n")
+assert deltas == ["+3", "+3", "+4", "-2", "-4"]
+assert day01.part1(deltas) == "4"
+assert day01.part2(deltas) == "10"
+
+day06 = aoc.Day06(max_distance=32)
+points = day06.parse("1, 1\n1, 6\n8, 3\n3, 4\n5, 5\n8, 9\n")
+assert day06.part1(points) == 17
+assert day06.part2(points) == 16
+
+day07 = aoc.Day07(workers=2, base_duration=0)
+assert (day07.workers, day07.base_duration) == (2, 0)
+steps = day07.parse("""Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.""")
+assert day07.part1(steps) == "CABDFE"
+assert day07.part2(steps) == "15"
+
+day08 = aoc.Day08()
+assert day08.part2(day08.parse("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2\n")) == 66
+
+day11 = aoc.Day11(width=300)
+assert day11.part1(day11.parse("18")) == (33, 45)
+
+assert issubclass(aoc.ParseError, ValueError)
+try:
+    day01.part1(day01.parse("+1\nnonsense\n"))
+except aoc.ParseError:
+    pass
+else:
+    raise AssertionError("expected ParseError for a malformed delta")
+
+try:
+    day01.part2(day01.parse("+1\n"))
+except ValueError:
+    pass
+else:
+    raise AssertionError("expected ValueError when nothing repeats")
+
+try:
+    day11.parse("not a serial")
+except aoc.ParseError:
+    pass
+else:
+    raise AssertionError("expected ParseError for a malformed serial")
+"#
+            ),
+            None,
+            None,
+        )
+        .unwrap_or_else(|e| {
+            e.display(py);
+            panic!("Python smoke test failed");
+        });
+    });
+}
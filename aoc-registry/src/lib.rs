@@ -0,0 +1,230 @@
+//! A single place to look up and run every solver by year, day and part.
+//!
+//! Each day's crate has its own signature (some take lines, some take the
+//! whole input, some take extra parameters), so every `Solution` wraps
+//! the day's solvers in a function from the raw puzzle input to the
+//! printed answer, using the same parameters as the day's `main`.
+use std::fmt;
+use std::panic;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn from_number(n: u8) -> Option<Part> {
+        match n {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// There's no solution registered for this year and day.
+    UnknownPuzzle { year: u16, day: u8 },
+    /// The solver rejected the input.
+    ///
+    /// The solvers panic on input they can't parse, so this carries the
+    /// panic message.
+    Input(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnknownPuzzle { year, day } => {
+                write!(f, "no solution registered for {} day {}", year, day)
+            }
+            Error::Input(msg) => write!(f, "invalid input: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+type Solver = fn(&str) -> String;
+
+pub struct Solution {
+    pub year: u16,
+    pub day: u8,
+    part1: Solver,
+    part2: Solver,
+}
+
+impl Solution {
+    /// Run one part of this solution against `input`.
+    pub fn solve(&self, part: Part, input: &str) -> Result<String, Error> {
+        let solver = match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        };
+        catch_input_errors(|| solver(input))
+    }
+}
+
+/// Run a solver, turning a panic into `Error::Input`.
+///
+/// The solvers `expect` their way through parsing, so a panic is how
+/// they report input they don't understand.
+pub fn catch_input_errors<T>(f: impl FnOnce() -> T + panic::UnwindSafe) -> Result<T, Error> {
+    panic::catch_unwind(f).map_err(|e| {
+        let msg = e
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "solver panicked".to_string());
+        Error::Input(msg)
+    })
+}
+
+fn lines(input: &str) -> Vec<&str> {
+    input.lines().collect()
+}
+
+/// Day 11's input is just the grid serial number.
+fn serial(input: &str) -> usize {
+    input
+        .trim()
+        .parse()
+        .expect("serial number must be a positive integer")
+}
+
+/// Day 1's first repeated frequency, found with the search which stops
+/// when nothing ever repeats, since that's an error like any other bad
+/// input.
+fn first_repeat(input: &str) -> String {
+    match day01::part2_analytic(&lines(input)) {
+        Some(frequency) => frequency.to_string(),
+        None => panic!("no frequency is ever repeated"),
+    }
+}
+
+static SOLUTIONS: &[Solution] = &[
+    Solution {
+        year: 2018,
+        day: 1,
+        part1: |input| day01::part1(&lines(input)).to_string(),
+        part2: first_repeat,
+    },
+    Solution {
+        year: 2018,
+        day: 2,
+        part1: |input| day02::part1(&lines(input)).to_string(),
+        part2: |input| day02::part2(&lines(input)),
+    },
+    Solution {
+        year: 2018,
+        day: 3,
+        part1: |input| day03::part1(&lines(input)).to_string(),
+        part2: |input| day03::part2(&lines(input)),
+    },
+    Solution {
+        year: 2018,
+        day: 4,
+        part1: |input| day04::part1(&lines(input)).to_string(),
+        part2: |input| day04::part2(&lines(input)).to_string(),
+    },
+    Solution {
+        year: 2018,
+        day: 5,
+        part1: |input| day05::part1(input.trim()).to_string(),
+        part2: |input| day05::part2(input.trim()).to_string(),
+    },
+    Solution {
+        year: 2018,
+        day: 6,
+        part1: |input| day06::part1(&lines(input)).to_string(),
+        part2: |input| day06::part2(&lines(input), 10000).to_string(),
+    },
+    Solution {
+        year: 2018,
+        day: 7,
+        part1: |input| day07::part1(&lines(input)),
+        part2: |input| day07::part2(&lines(input), 5, 60),
+    },
+    Solution {
+        year: 2018,
+        day: 8,
+        part1: |input| day08::part1(input).to_string(),
+        part2: |input| day08::part2(input).to_string(),
+    },
+    Solution {
+        year: 2018,
+        day: 11,
+        part1: |input| {
+            let (x, y) = day11::part1(serial(input), 300, 3);
+            format!("{},{}", x, y)
+        },
+        part2: |input| {
+            let (x, y, size) = day11::part2(serial(input), 300);
+            format!("{},{},{}", x, y, size)
+        },
+    },
+];
+
+/// Every registered solution, ordered by year and day.
+pub fn solutions() -> &'static [Solution] {
+    SOLUTIONS
+}
+
+pub fn find(year: u16, day: u8) -> Option<&'static Solution> {
+    SOLUTIONS.iter().find(|s| s.year == year && s.day == day)
+}
+
+/// Look up a solution and run one part of it against `input`.
+pub fn solve(year: u16, day: u8, part: Part, input: &str) -> Result<String, Error> {
+    find(year, day)
+        .ok_or(Error::UnknownPuzzle { year, day })?
+        .solve(part, input)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{solve, Error, Part};
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(
+            solve(2018, 1, Part::One, "+1\n+1\n-2\n"),
+            Ok("0".to_string())
+        );
+        assert_eq!(
+            solve(2018, 5, Part::Two, "dabAcCaCBAcCcaDA\n"),
+            Ok("4".to_string())
+        );
+        assert_eq!(
+            solve(2018, 8, Part::Two, "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2"),
+            Ok("66".to_string())
+        );
+        assert_eq!(solve(2018, 11, Part::One, "18\n"), Ok("33,45".to_string()));
+    }
+
+    #[test]
+    fn test_unknown_puzzle() {
+        assert_eq!(
+            solve(2018, 25, Part::One, ""),
+            Err(Error::UnknownPuzzle {
+                year: 2018,
+                day: 25
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        match solve(2018, 1, Part::One, "+1\nnot a number\n") {
+            Err(Error::Input(_)) => {}
+            other => panic!("expected an input error, got {:?}", other),
+        }
+        assert_eq!(
+            solve(2018, 1, Part::Two, "+1\n"),
+            Err(Error::Input("no frequency is ever repeated".to_string()))
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Pseudo-random numbers for generating test and benchmark inputs.
+//!
+//! Only meant as a dev-dependency: it's `no_std` so it never gets in the
+//! way of the days' `no_std` builds, and it isn't good randomness, just
+//! the same on every run.
+#![no_std]
+
+/// A small 64-bit LCG, with Knuth's MMIX constants.
+#[derive(Clone, Debug)]
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg { state: seed }
+    }
+
+    /// A number in `0..n`, from the top 31 bits of the state, since the
+    /// low bits of an LCG repeat quickly.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    pub fn below(&mut self, n: u32) -> u32 {
+        self.state = self
+            .state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        let bits = (self.state >> 33) as u32;
+        bits % n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Lcg;
+
+    #[test]
+    fn test_lcg() {
+        let mut a = Lcg::new(12345);
+        let mut b = a.clone();
+        for _ in 0..1000 {
+            let n = a.below(200_001);
+            assert!(n < 200_001);
+            assert_eq!(n, b.below(200_001));
+        }
+        // Values spread past 16 bits, for the larger inputs.
+        assert!((0..100).any(|_| a.below(1 << 30) >= 1 << 16));
+        assert_ne!(Lcg::new(1).below(1000), Lcg::new(2).below(1000));
+    }
+}
@@ -0,0 +1,241 @@
+//! Typed execution traces for the solvers.
+//!
+//! Solvers which support tracing take a `&mut impl Tracer` and record an
+//! [`Event`] at each interesting step. A [`Trace`] collects those events,
+//! can be written to and read from JSON Lines, and two traces can be
+//! compared with [`first_divergence`] to find where an optimised variant
+//! stops agreeing with the reference implementation.
+//!
+//! Without the default `std` feature this builds as `no_std`, leaving out
+//! the JSON Lines support.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A worker picked up a task (day 7).
+    TaskStarted {
+        worker: usize,
+        task: char,
+        time: usize,
+    },
+    /// A worker completed a task (day 7).
+    TaskFinished {
+        worker: usize,
+        task: char,
+        time: usize,
+    },
+    /// A guard began a shift (day 4).
+    ShiftStarted { guard: u32, time: String },
+    /// A guard fell asleep (day 4).
+    GuardFellAsleep { guard: u32, time: String },
+    /// A guard woke up (day 4).
+    GuardWokeUp { guard: u32, time: String },
+    /// Two adjacent units of opposite polarity destroyed each other (day 5).
+    ///
+    /// `index` is the position in the input of the unit which triggered
+    /// the reaction.
+    UnitsReacted {
+        index: usize,
+        left: char,
+        right: char,
+    },
+}
+
+/// Something which can receive trace events from a solver.
+pub trait Tracer {
+    fn record(&mut self, event: Event);
+}
+
+impl<T: Tracer + ?Sized> Tracer for &mut T {
+    fn record(&mut self, event: Event) {
+        (**self).record(event)
+    }
+}
+
+/// A tracer which throws every event away.
+///
+/// The untraced solver entry points use this, so tracing costs nothing
+/// unless it's asked for.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopTracer;
+
+impl Tracer for NoopTracer {
+    #[inline(always)]
+    fn record(&mut self, _event: Event) {}
+}
+
+/// An in-memory, ordered record of events.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    events: Vec<Event>,
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Trace::default()
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Write the trace as JSON Lines, one event per line.
+    #[cfg(feature = "std")]
+    pub fn write_jsonl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for event in &self.events {
+            serde_json::to_writer(&mut writer, event)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Read a trace previously written by `write_jsonl`.
+    ///
+    /// Blank lines are skipped; anything else which isn't a valid event
+    /// is reported as `InvalidData`.
+    #[cfg(feature = "std")]
+    pub fn read_jsonl<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut events = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(serde_json::from_str(&line)?);
+        }
+        Ok(Trace { events })
+    }
+
+    /// Feed every recorded event, in order, into another tracer.
+    pub fn replay(&self, tracer: &mut impl Tracer) {
+        for event in &self.events {
+            tracer.record(event.clone());
+        }
+    }
+}
+
+impl Tracer for Trace {
+    fn record(&mut self, event: Event) {
+        self.events.push(event);
+    }
+}
+
+/// The first point at which two traces disagree.
+///
+/// Either side is `None` if that trace ended before the other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub index: usize,
+    pub expected: Option<Event>,
+    pub actual: Option<Event>,
+}
+
+/// Find the first event at which `actual` differs from `expected`.
+///
+/// Returns `None` if the traces are identical.
+pub fn first_divergence(expected: &[Event], actual: &[Event]) -> Option<Divergence> {
+    let mut expected_iter = expected.iter();
+    let mut actual_iter = actual.iter();
+    let mut index = 0;
+    loop {
+        match (expected_iter.next(), actual_iter.next()) {
+            (None, None) => return None,
+            (e, a) if e == a => index += 1,
+            (e, a) => {
+                return Some(Divergence {
+                    index,
+                    expected: e.cloned(),
+                    actual: a.cloned(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use alloc::string::ToString;
+
+    use super::{first_divergence, Divergence, Event, Trace, Tracer};
+
+    fn sample() -> Trace {
+        let mut trace = Trace::new();
+        trace.record(Event::TaskStarted {
+            worker: 0,
+            task: 'C',
+            time: 0,
+        });
+        trace.record(Event::GuardFellAsleep {
+            guard: 10,
+            time: "1518-11-01 00:05".to_string(),
+        });
+        trace.record(Event::UnitsReacted {
+            index: 3,
+            left: 'c',
+            right: 'C',
+        });
+        trace
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_jsonl_roundtrip() {
+        let trace = sample();
+        let mut buf = Vec::new();
+        trace.write_jsonl(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert_eq!(
+            text.lines().next().unwrap(),
+            r#"{"event":"task_started","worker":0,"task":"C","time":0}"#
+        );
+        assert_eq!(Trace::read_jsonl(text.as_bytes()).unwrap(), trace);
+    }
+
+    #[test]
+    fn test_replay() {
+        let trace = sample();
+        let mut copy = Trace::new();
+        trace.replay(&mut copy);
+        assert_eq!(copy, trace);
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let expected = sample();
+        assert_eq!(first_divergence(expected.events(), expected.events()), None);
+
+        let mut actual = expected.events().to_vec();
+        actual[1] = Event::GuardWokeUp {
+            guard: 10,
+            time: "1518-11-01 00:05".to_string(),
+        };
+        assert_eq!(
+            first_divergence(expected.events(), &actual),
+            Some(Divergence {
+                index: 1,
+                expected: Some(expected.events()[1].clone()),
+                actual: Some(actual[1].clone()),
+            })
+        );
+
+        assert_eq!(
+            first_divergence(expected.events(), &expected.events()[..2]),
+            Some(Divergence {
+                index: 2,
+                expected: Some(expected.events()[2].clone()),
+                actual: None,
+            })
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::process;
+
+use aoc_trace::{first_divergence, Trace};
+
+fn load(path: &str) -> Trace {
+    let file = File::open(path).unwrap_or_else(|e| {
+        eprintln!("could not open {}: {}", path, e);
+        process::exit(2);
+    });
+    Trace::read_jsonl(BufReader::new(file)).unwrap_or_else(|e| {
+        eprintln!("could not read trace from {}: {}", path, e);
+        process::exit(2);
+    })
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <reference.jsonl> <candidate.jsonl>", args[0]);
+        process::exit(2);
+    }
+    let expected = load(&args[1]);
+    let actual = load(&args[2]);
+    match first_divergence(expected.events(), actual.events()) {
+        None => println!("Traces match ({} events)", expected.events().len()),
+        Some(divergence) => {
+            println!("Traces diverge at event {}", divergence.index);
+            println!("  expected: {:?}", divergence.expected);
+            println!("  actual:   {:?}", divergence.actual);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! A `wasm-bindgen` facade over the solver registry, for use from
+//! JavaScript.
+//!
+//! Build with `cargo build -p aoc-wasm --target wasm32-unknown-unknown`
+//! and run the output through `wasm-bindgen`. The registry is pulled in
+//! without its `parallel` feature, since there are no threads to hand
+//! work to in the browser.
+use wasm_bindgen::prelude::*;
+
+use aoc_registry::Part;
+
+/// Solve one part of a 2018 puzzle, returning the answer as a string.
+///
+/// Throws if the day isn't implemented or `part` isn't 1 or 2.
+///
+/// Note that `wasm32-unknown-unknown` aborts on panic rather than
+/// unwinding, so input which the solver can't parse traps instead of
+/// throwing a catchable error.
+#[wasm_bindgen]
+pub fn solve(day: u8, part: u8, input: &str) -> Result<String, JsValue> {
+    let part = Part::from_number(part)
+        .ok_or_else(|| JsValue::from_str(&format!("invalid part {}", part)))?;
+    aoc_registry::solve(2018, day, part, input).map_err(|e| JsValue::from_str(&e.to_string()))
+}
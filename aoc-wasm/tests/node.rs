@@ -0,0 +1,22 @@
+//! Run with `cargo test -p aoc-wasm --target wasm32-unknown-unknown`.
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use aoc_wasm::solve;
+
+#[wasm_bindgen_test]
+fn test_solve() {
+    assert_eq!(solve(1, 2, "+3\n+3\n+4\n-2\n-4\n").unwrap(), "10");
+    assert_eq!(
+        solve(3, 1, "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2\n").unwrap(),
+        "4"
+    );
+    assert_eq!(solve(11, 1, "18").unwrap(), "33,45");
+}
+
+#[wasm_bindgen_test]
+fn test_solve_errors() {
+    assert!(solve(1, 3, "+1").is_err());
+    assert!(solve(25, 1, "").is_err());
+}
@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use day01::{part1, part2_functional, part2_imperative};
+use day01::{part1, part2_analytic, part2_functional, part2_imperative};
 
 fn part1_benchmark(c: &mut Criterion) {
     let input: Vec<&str> = include_str!("../../input/2018/day1.txt").lines().collect();
@@ -20,10 +20,16 @@ fn part2_imperative_benchmark(c: &mut Criterion) {
     });
 }
 
+fn part2_analytic_benchmark(c: &mut Criterion) {
+    let input: Vec<&str> = include_str!("../../input/2018/day1.txt").lines().collect();
+    c.bench_function("part2_analytic", move |b| b.iter(|| part2_analytic(&input)));
+}
+
 criterion_group!(
     benches,
     part1_benchmark,
     part2_functional_benchmark,
-    part2_imperative_benchmark
+    part2_imperative_benchmark,
+    part2_analytic_benchmark
 );
 criterion_main!(benches);
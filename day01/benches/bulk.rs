@@ -0,0 +1,83 @@
+//! Benchmarks for `sum_file` on generated inputs.
+//!
+//! The inputs are written to the temp directory on first use and kept
+//! for later runs. By default they have 10^6 and 10^7 lines; set
+//! `DAY01_BULK_LINES` to a comma-separated list of line counts to
+//! change that, e.g. `DAY01_BULK_LINES=1000000000` for a ~4.5GB file.
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use aoc_testgen::Lcg;
+use criterion::{criterion_group, criterion_main, Criterion, ParameterizedBenchmark, Throughput};
+use day01::{part1, sum_file};
+
+fn input_path(lines: u32) -> PathBuf {
+    let path = env::temp_dir().join(format!("day01-bulk-bench-{}.txt", lines));
+    if !path.exists() {
+        let partial = path.with_extension("partial");
+        let mut out = BufWriter::new(File::create(&partial).unwrap());
+        let mut rng = Lcg::new(12345);
+        for _ in 0..lines {
+            match i64::from(rng.below(200_001)) - 100_000 {
+                d if d >= 0 => writeln!(out, "+{}", d).unwrap(),
+                d => writeln!(out, "{}", d).unwrap(),
+            }
+        }
+        out.flush().unwrap();
+        fs::rename(&partial, &path).unwrap();
+    }
+    path
+}
+
+fn line_counts() -> Vec<u32> {
+    env::var("DAY01_BULK_LINES")
+        .unwrap_or_else(|_| "1000000,10000000".to_string())
+        .split(',')
+        .map(|n| {
+            n.trim()
+                .parse()
+                .expect("DAY01_BULK_LINES must be line counts")
+        })
+        .collect()
+}
+
+fn sum_file_benchmark(c: &mut Criterion) {
+    let counts = line_counts();
+    let paths: Vec<(u32, PathBuf)> = counts.iter().map(|&n| (n, input_path(n))).collect();
+    c.bench(
+        "sum_file",
+        ParameterizedBenchmark::new(
+            "sum_file",
+            move |b, &lines| {
+                let path = &paths.iter().find(|(n, _)| *n == lines).unwrap().1;
+                b.iter(|| sum_file(path).unwrap())
+            },
+            counts,
+        )
+        .throughput(|&lines| Throughput::Elements(lines))
+        .sample_size(10),
+    );
+}
+
+fn part1_benchmark(c: &mut Criterion) {
+    // The baseline, on the smallest input since it needs every line in
+    // memory at once.
+    let lines = 1_000_000;
+    // Leaked to live as long as the `include_str!` inputs of the other
+    // benches.
+    let text: &'static str = Box::leak(fs::read_to_string(input_path(lines)).unwrap().into());
+    let input: Vec<&str> = text.lines().collect();
+    let expected = sum_file(input_path(lines)).unwrap();
+    assert_eq!(i64::from(part1(&input)), expected);
+    c.bench(
+        "sum_file",
+        ParameterizedBenchmark::new("part1", move |b, _| b.iter(|| part1(&input)), vec![lines])
+            .throughput(|&lines| Throughput::Elements(lines))
+            .sample_size(10),
+    );
+}
+
+criterion_group!(benches, sum_file_benchmark, part1_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,270 @@
+//! Summing delta files far bigger than the puzzle input.
+//!
+//! The file is memory-mapped and split into chunks on line boundaries,
+//! and rayon sums the chunks in parallel with a hand-written parser, so
+//! nothing is allocated per line.
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+use crate::Error;
+
+/// Chunks are at least this big, so each rayon task has real work to do.
+const MIN_CHUNK_LEN: usize = 1 << 20;
+
+/// Parse one change as written in the puzzle input, e.g. `+3` or `-4`.
+///
+/// Returns `None` for anything else, including a value which doesn't
+/// fit in an `i64`.
+pub fn parse_delta(bytes: &[u8]) -> Option<i64> {
+    let (negative, digits) = match bytes.split_first() {
+        Some((b'+', rest)) => (false, rest),
+        Some((b'-', rest)) => (true, rest),
+        _ => (false, bytes),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    // Accumulate towards the sign of the result, so `i64::MIN` parses.
+    let mut value: i64 = 0;
+    for &b in digits {
+        let digit = match b {
+            b'0'..=b'9' => i64::from(b - b'0'),
+            _ => return None,
+        };
+        value = value.checked_mul(10)?;
+        value = if negative {
+            value.checked_sub(digit)?
+        } else {
+            value.checked_add(digit)?
+        };
+    }
+    Some(value)
+}
+
+/// The lines of `input`, without their terminators, skipping blank ones.
+fn lines(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    input
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.is_empty())
+}
+
+/// The sum of a run of lines, with the lowest and highest running totals
+/// along the way, all relative to the frequency the run starts at.
+#[derive(Clone, Copy)]
+struct Span {
+    sum: i64,
+    min: i64,
+    max: i64,
+}
+
+impl Span {
+    const EMPTY: Span = Span {
+        sum: 0,
+        min: 0,
+        max: 0,
+    };
+
+    /// This run followed by `next`, or `None` if a running total doesn't
+    /// fit in an `i64`.
+    fn then(self, next: Span) -> Option<Span> {
+        Some(Span {
+            sum: self.sum.checked_add(next.sum)?,
+            min: self.min.min(self.sum.checked_add(next.min)?),
+            max: self.max.max(self.sum.checked_add(next.max)?),
+        })
+    }
+}
+
+/// Sum one chunk, or `None` if a line doesn't parse or a running total
+/// overflows.
+fn sum_chunk(chunk: &[u8]) -> Option<Span> {
+    lines(chunk).try_fold(Span::EMPTY, |span, line| {
+        let delta = parse_delta(line)?;
+        span.then(Span {
+            sum: delta,
+            min: delta,
+            max: delta,
+        })
+    })
+}
+
+/// Sum `input` one line at a time, finding exactly where it goes wrong.
+fn sum_sequential(input: &[u8]) -> Result<i64, Error> {
+    let mut sum: i64 = 0;
+    for (deltas_applied, line) in lines(input).enumerate() {
+        let delta = parse_delta(line).ok_or_else(|| Error::Parse {
+            line: input[..offset_in(input, line)]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count(),
+            element: String::from_utf8_lossy(line).into_owned(),
+        })?;
+        sum = sum.checked_add(delta).ok_or(Error::Overflow {
+            deltas_applied: deltas_applied + 1,
+        })?;
+    }
+    Ok(sum)
+}
+
+fn offset_in(input: &[u8], line: &[u8]) -> usize {
+    line.as_ptr() as usize - input.as_ptr() as usize
+}
+
+/// Split `input` into roughly `n` chunks, each ending just after a
+/// newline (or at the end of the input).
+fn chunks(input: &[u8], n: usize) -> Vec<&[u8]> {
+    let target = (input.len() / n.max(1)).max(MIN_CHUNK_LEN);
+    let mut chunks = Vec::with_capacity(n);
+    let mut rest = input;
+    while rest.len() > target {
+        let end = match rest[target..].iter().position(|&b| b == b'\n') {
+            Some(newline) => target + newline + 1,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+/// Sum the changes in `input`, one per line, in parallel.
+///
+/// Gives the same answer as `part1` for any input `part1` accepts, except
+/// that blank lines are skipped and the sum is an `i64`. Each chunk keeps
+/// track of its lowest and highest running totals as well as its sum, so
+/// adding the chunks up checks every running total, just as summing in
+/// order would. If one doesn't fit, or a line doesn't parse, the input is
+/// summed again in order to report exactly where.
+pub fn sum_bytes(input: &[u8]) -> Result<i64, Error> {
+    let total = chunks(input, rayon::current_num_threads() * 4)
+        .into_par_iter()
+        .map(sum_chunk)
+        .try_reduce(|| Span::EMPTY, Span::then);
+    match total {
+        Some(total) => Ok(total.sum),
+        None => sum_sequential(input),
+    }
+}
+
+/// Memory-map the file at `path` and sum the changes in it with
+/// `sum_bytes`.
+///
+/// Input errors are returned as `InvalidData`.
+pub fn sum_file<P: AsRef<Path>>(path: P) -> io::Result<i64> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        // Mapping an empty file fails on some platforms.
+        return Ok(0);
+    }
+    // Safety: the map is only read while we hold it, and nothing in this
+    // process writes to the file. Another process truncating it under us
+    // is the usual caveat of memory-mapping.
+    let map = unsafe { Mmap::map(&file)? };
+    sum_bytes(&map).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use std::io::Write;
+
+    use super::{chunks, parse_delta, sum_bytes, sum_file};
+    use crate::{part1, Error};
+
+    #[test]
+    fn test_parse_delta() {
+        assert_eq!(parse_delta(b"+3"), Some(3));
+        assert_eq!(parse_delta(b"-4"), Some(-4));
+        assert_eq!(parse_delta(b"17"), Some(17));
+        assert_eq!(parse_delta(b"-9223372036854775808"), Some(i64::MIN));
+        assert_eq!(parse_delta(b"+9223372036854775807"), Some(i64::MAX));
+        assert_eq!(parse_delta(b"+9223372036854775808"), None);
+        assert_eq!(parse_delta(b""), None);
+        assert_eq!(parse_delta(b"+"), None);
+        assert_eq!(parse_delta(b"+-1"), None);
+        assert_eq!(parse_delta(b"1 "), None);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let input: Vec<u8> = (0..300_000)
+            .flat_map(|i| format!("+{}\n", i % 97).into_bytes())
+            .collect();
+        let split = chunks(&input, 4);
+        assert!(split.len() > 1);
+        assert_eq!(split.concat(), input);
+        assert!(split.iter().all(|chunk| chunk.ends_with(b"\n")));
+    }
+
+    #[test]
+    fn test_sum_bytes_matches_part1() {
+        let lines: Vec<String> = (0..400_000i64)
+            .map(|i| match (i * 7919) % 201 - 100 {
+                d if d >= 0 => format!("+{}", d),
+                d => d.to_string(),
+            })
+            .collect();
+        let input: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let bytes = lines.join("\n");
+        assert_eq!(sum_bytes(bytes.as_bytes()), Ok(i64::from(part1(&input))));
+        assert_eq!(sum_bytes(b"+1\r\n\r\n-3\r\n"), Ok(-2));
+    }
+
+    #[test]
+    fn test_sum_bytes_errors() {
+        assert_eq!(
+            sum_bytes(b"+1\n+2\n\nthree\n"),
+            Err(Error::Parse {
+                line: 3,
+                element: "three".into()
+            })
+        );
+        assert_eq!(
+            sum_bytes(b"+9223372036854775807\n+1\n"),
+            Err(Error::Overflow { deltas_applied: 2 })
+        );
+        // Past `i32::MAX` is fine.
+        assert_eq!(
+            sum_bytes(b"+2147483647\n+2147483647\n"),
+            Ok(2 * i64::from(i32::MAX))
+        );
+    }
+
+    #[test]
+    fn test_sum_bytes_overflow_across_chunks() {
+        // The total fits, and so does each chunk's sum, but the running
+        // total overflows in a later chunk than the one which takes it up
+        // to `i64::MAX`.
+        let padding = "+0\n".repeat(500_000);
+        let input = format!("+9223372036854775807\n{}+1\n-1\n", padding);
+        assert!(chunks(input.as_bytes(), 4).len() > 1);
+        assert_eq!(
+            sum_bytes(input.as_bytes()),
+            Err(Error::Overflow {
+                deltas_applied: 500_002
+            })
+        );
+        let input = format!("+9223372036854775807\n{}-1\n+1\n", padding);
+        assert_eq!(sum_bytes(input.as_bytes()), Ok(i64::MAX));
+    }
+
+    #[test]
+    fn test_sum_file() {
+        let path = std::env::temp_dir().join(format!("day01-bulk-{}.txt", std::process::id()));
+        let mut file = fs::File::create(&path).unwrap();
+        file.write_all(b"+1\n-2\n+3\n").unwrap();
+        drop(file);
+        assert_eq!(sum_file(&path).unwrap(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
+
+use hashbrown::HashMap;
+
+use crate::frequency::{Error, Frequency};
+
+/// Emitted the first time the calibrator reaches a frequency it has
+/// already seen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Repeat<T = i32> {
+    pub frequency: T,
+    /// How many deltas had been applied when the frequency was first
+    /// reached (0 for the starting frequency).
+    pub first_seen: usize,
+    /// How many deltas had been applied when it was reached again.
+    pub repeated_at: usize,
+}
+
+/// Applies frequency changes one at a time, keeping track of what it
+/// has seen along the way.
+///
+/// This never needs the whole input at once, so it can calibrate
+/// against an unbounded stream of deltas. It does remember every distinct
+/// frequency it reaches, though, so its memory grows with the number of
+/// them: without limit on a stream which keeps drifting. Frequencies are
+/// accumulated in `T`; `try_push` reports a change which takes the
+/// frequency out of its range instead of wrapping.
+#[derive(Debug)]
+pub struct Calibrator<T = i32> {
+    frequency: T,
+    min: T,
+    max: T,
+    deltas_applied: usize,
+    // For each frequency, how often it's been reached (saturating at
+    // `u32::MAX`) and after how many deltas it was first reached.
+    visits: HashMap<T, (u32, usize)>,
+    first_repeat: Option<Repeat<T>>,
+}
+
+impl<T: Frequency> Default for Calibrator<T> {
+    fn default() -> Self {
+        Calibrator::new()
+    }
+}
+
+impl<T: Frequency> Calibrator<T> {
+    /// Start a calibrator at frequency zero, which counts as visited.
+    pub fn new() -> Self {
+        let mut visits = HashMap::new();
+        visits.insert(T::zero(), (1, 0));
+        Calibrator {
+            frequency: T::zero(),
+            min: T::zero(),
+            max: T::zero(),
+            deltas_applied: 0,
+            visits,
+            first_repeat: None,
+        }
+    }
+
+    /// Apply a single change.
+    ///
+    /// Returns the repeat event if this is the first time any frequency
+    /// has been reached twice, and `None` otherwise (including for any
+    /// later repeats).
+    ///
+    /// # Panics
+    ///
+    /// If the frequency overflows `T`; use `try_push` to handle that.
+    pub fn push(&mut self, delta: T) -> Option<Repeat<T>> {
+        self.try_push(delta).expect("frequency overflowed")
+    }
+
+    /// Apply a single change, or return `Error::Overflow` and leave the
+    /// calibrator untouched if the new frequency doesn't fit in `T`.
+    pub fn try_push(&mut self, delta: T) -> Result<Option<Repeat<T>>, Error> {
+        let frequency = self.frequency.checked_add(&delta).ok_or(Error::Overflow {
+            deltas_applied: self.deltas_applied + 1,
+        })?;
+        self.deltas_applied += 1;
+        if frequency < self.min {
+            self.min = frequency.clone();
+        }
+        if frequency > self.max {
+            self.max = frequency.clone();
+        }
+        self.frequency = frequency;
+        let deltas_applied = self.deltas_applied;
+        let visit = self
+            .visits
+            .entry(self.frequency.clone())
+            .or_insert((0, deltas_applied));
+        visit.0 = visit.0.saturating_add(1);
+        if visit.0 == 2 && self.first_repeat.is_none() {
+            let repeat = Repeat {
+                frequency: self.frequency.clone(),
+                first_seen: visit.1,
+                repeated_at: deltas_applied,
+            };
+            self.first_repeat = Some(repeat.clone());
+            return Ok(Some(repeat));
+        }
+        Ok(None)
+    }
+
+    /// Apply every change in `reader`, one per line.
+    ///
+    /// Blank lines are skipped. Returns the repeat event if it happened
+    /// while reading, and an `InvalidData` error for any line which isn't
+    /// a signed integer or which overflows `T`.
+    #[cfg(feature = "std")]
+    pub fn feed<R: BufRead>(&mut self, reader: R) -> io::Result<Option<Repeat<T>>> {
+        let mut repeat = None;
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let delta = line.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid frequency change {:?}", line),
+                )
+            })?;
+            let pushed = self
+                .try_push(delta)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            repeat = repeat.or(pushed);
+        }
+        Ok(repeat)
+    }
+
+    /// Apply a change written as in the puzzle input, e.g. `+3` or `-4`.
+    pub fn push_str(&mut self, element: &str) -> Option<Repeat<T>> {
+        match element.parse() {
+            Ok(delta) => self.push(delta),
+            Err(_) => panic!("invalid frequency change {:?}", element),
+        }
+    }
+
+    pub fn frequency(&self) -> T {
+        self.frequency.clone()
+    }
+
+    pub fn min(&self) -> T {
+        self.min.clone()
+    }
+
+    pub fn max(&self) -> T {
+        self.max.clone()
+    }
+
+    pub fn deltas_applied(&self) -> usize {
+        self.deltas_applied
+    }
+
+    /// How many times `frequency` has been reached, up to `u32::MAX`.
+    pub fn visits(&self, frequency: &T) -> u32 {
+        self.visits.get(frequency).map_or(0, |v| v.0)
+    }
+
+    /// Every frequency reached so far, with how many times it's been
+    /// reached, up to `u32::MAX`.
+    pub fn visit_counts(&self) -> impl Iterator<Item = (T, u32)> + '_ {
+        self.visits
+            .iter()
+            .map(|(frequency, v)| (frequency.clone(), v.0))
+    }
+
+    pub fn first_repeat(&self) -> Option<Repeat<T>> {
+        self.first_repeat.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Calibrator, Repeat};
+    use crate::Error;
+
+    #[test]
+    fn test_push() {
+        let mut calibrator = Calibrator::new();
+        assert_eq!(calibrator.push(3), None);
+        assert_eq!(calibrator.push(-5), None);
+        assert_eq!(
+            calibrator.push(2),
+            Some(Repeat {
+                frequency: 0,
+                first_seen: 0,
+                repeated_at: 3
+            })
+        );
+        // Only the first repeat is reported.
+        assert_eq!(calibrator.push(3), None);
+        assert_eq!(calibrator.frequency(), 3);
+        assert_eq!((calibrator.min(), calibrator.max()), (-2, 3));
+        assert_eq!(calibrator.visits(&3), 2);
+        assert_eq!(calibrator.visits(&7), 0);
+        assert_eq!(calibrator.deltas_applied(), 4);
+        assert_eq!(calibrator.first_repeat().map(|r| r.frequency), Some(0));
+    }
+
+    #[test]
+    fn test_try_push_overflow() {
+        let mut calibrator = Calibrator::<i32>::new();
+        assert_eq!(calibrator.try_push(i32::MAX), Ok(None));
+        assert_eq!(
+            calibrator.try_push(1),
+            Err(Error::Overflow { deltas_applied: 2 })
+        );
+        // The failed change wasn't applied.
+        assert_eq!(calibrator.frequency(), i32::MAX);
+        assert_eq!(calibrator.deltas_applied(), 1);
+
+        let mut calibrator = Calibrator::<i64>::new();
+        calibrator.push(i64::from(i32::MAX));
+        calibrator.push(1);
+        assert_eq!(calibrator.frequency(), i64::from(i32::MAX) + 1);
+    }
+
+    #[test]
+    fn test_visits_saturate() {
+        let mut calibrator = Calibrator::<i32>::new();
+        calibrator.visits.insert(1, (u32::MAX - 1, 0));
+        calibrator.push(1);
+        calibrator.push(-1);
+        calibrator.push(1);
+        assert_eq!(calibrator.visits(&1), u32::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_feed() {
+        let mut calibrator = Calibrator::new();
+        let repeat = calibrator
+            .feed("+3\n+3\n\n+4\n-2\n-4\n".as_bytes())
+            .unwrap();
+        assert_eq!(repeat, None);
+        let repeat = calibrator.feed("+3\n+3\n+4\n".as_bytes()).unwrap();
+        assert_eq!(
+            repeat,
+            Some(Repeat {
+                frequency: 10,
+                first_seen: 3,
+                repeated_at: 7
+            })
+        );
+        assert!(calibrator.feed("+1\nfive\n".as_bytes()).is_err());
+        assert!(calibrator.feed("+2147483647\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use alloc::string::String;
+use core::fmt;
+use core::hash::Hash;
+use core::str::FromStr;
+
+/// A type frequencies can be accumulated in.
+///
+/// Implemented for `i32`, `i64` and `i128`, and for `num_bigint::BigInt`
+/// with the `bigint` feature.
+pub trait Frequency: Clone + Ord + Hash + FromStr {
+    fn zero() -> Self;
+
+    /// `self + rhs`, or `None` if the result doesn't fit.
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+}
+
+macro_rules! impl_frequency {
+    ($($t:ty),*) => {
+        $(
+            impl Frequency for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                    <$t>::checked_add(*self, *rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_frequency!(i32, i64, i128);
+
+#[cfg(feature = "bigint")]
+impl Frequency for num_bigint::BigInt {
+    fn zero() -> Self {
+        num_bigint::BigInt::from(0)
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The line at `line` (zero-based) isn't a frequency change.
+    Parse { line: usize, element: String },
+    /// Applying the change numbered `deltas_applied` (counting from one)
+    /// took the frequency out of the accumulator's range.
+    Overflow { deltas_applied: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse { line, element } => {
+                write!(
+                    f,
+                    "invalid frequency change {:?} on line {}",
+                    element,
+                    line + 1
+                )
+            }
+            Error::Overflow { deltas_applied } => {
+                write!(f, "frequency overflowed after {} changes", deltas_applied)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -1,47 +1,160 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
 use hashbrown::HashSet;
 
-fn parse_element(el: &str) -> i32 {
-    el.parse().unwrap()
+#[cfg(feature = "parallel")]
+mod bulk;
+mod calibrator;
+mod frequency;
+mod repeats;
+
+#[cfg(feature = "parallel")]
+pub use bulk::{parse_delta, sum_bytes, sum_file};
+pub use calibrator::{Calibrator, Repeat};
+pub use frequency::{Error, Frequency};
+pub use repeats::{first_repeats, part2_report, visit_counts, Position, RepeatReport};
+
+fn parse_element<T: Frequency>(line: usize, element: &str) -> Result<T, Error> {
+    element.parse().map_err(|_| Error::Parse {
+        line,
+        element: String::from(element),
+    })
+}
+
+fn parse_all<T: Frequency>(input: &[&str]) -> Result<Vec<T>, Error> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(line, element)| parse_element(line, element))
+        .collect()
 }
 
+/// # Panics
+///
+/// If a line isn't a frequency change or the frequency overflows an
+/// `i32`; use `part1_checked` to handle those.
 pub fn part1(input: &[&str]) -> i32 {
-    input.iter().map(|el| parse_element(el)).sum()
+    part1_checked(input).unwrap_or_else(|e| panic!("{}", e))
 }
 
-pub fn part2_functional(input: &[&str]) -> i32 {
+/// `part1`, accumulating in `T` and reporting overflow instead of
+/// wrapping.
+pub fn part1_checked<T: Frequency>(input: &[&str]) -> Result<T, Error> {
+    input
+        .iter()
+        .enumerate()
+        .try_fold(T::zero(), |frequency, (line, element)| {
+            let delta = parse_element(line, element)?;
+            frequency.checked_add(&delta).ok_or(Error::Overflow {
+                deltas_applied: line + 1,
+            })
+        })
+}
+
+/// The first frequency reached twice, or `Ok(None)` if `input` is empty.
+///
+/// Any other input which never repeats loops forever; `part2_checked` and
+/// `part2_analytic` stop instead.
+pub fn part2_functional(input: &[&str]) -> Result<Option<i32>, Error> {
+    let deltas: Vec<i32> = parse_all(input)?;
     let mut seen = HashSet::new();
     seen.insert(0);
-    let err: Result<_, i32> = input.iter().cycle().try_fold(0, |acc, &x| {
-        let new = acc + parse_element(x);
-        if seen.contains(&new) {
-            return Err(new);
-        } else {
-            seen.insert(new);
-            return Ok(new);
-        }
-    });
-    return err.err().unwrap();
+    let mut frequency: i32 = 0;
+    deltas
+        .iter()
+        .cycle()
+        .enumerate()
+        .map(|(i, delta)| {
+            frequency = frequency.checked_add(*delta).ok_or(Error::Overflow {
+                deltas_applied: i + 1,
+            })?;
+            Ok(frequency)
+        })
+        .find(|frequency| frequency.as_ref().map_or(true, |&f| !seen.insert(f)))
+        .transpose()
 }
 
 pub fn part2_imperative(input: &[&str]) -> i32 {
-    let mut current = 0;
-    let mut seen = HashSet::new();
-    seen.insert(current);
-    let repeated = input.iter().cycle();
-    for element in repeated {
-        current = current + parse_element(element);
-        if seen.contains(&current) {
-            return current;
+    let mut calibrator = Calibrator::new();
+    for element in input.iter().cycle() {
+        if let Some(repeat) = calibrator.push_str(element) {
+            return repeat.frequency;
         }
-        seen.insert(current);
     }
     unreachable!()
 }
 
+/// `part2`, accumulating in `T` and reporting overflow instead of
+/// wrapping.
+///
+/// Returns `Ok(None)` if no frequency is ever repeated. Every repeat
+/// pairs two first-pass frequencies an exact number of drifts apart, so
+/// once the drift has carried the frequency further than the span of the
+/// first pass there's nothing left to find.
+pub fn part2_checked<T: Frequency>(input: &[&str]) -> Result<Option<T>, Error> {
+    let deltas: Vec<T> = parse_all(input)?;
+    if deltas.is_empty() {
+        return Ok(None);
+    }
+    let mut calibrator = Calibrator::new();
+    let mut span = None;
+    loop {
+        for delta in &deltas {
+            if let Some(repeat) = calibrator.try_push(delta.clone())? {
+                return Ok(Some(repeat.frequency));
+            }
+        }
+        let (min, max) = span.get_or_insert_with(|| (calibrator.min(), calibrator.max()));
+        // Neither sum can overflow, since `min <= 0 <= max`.
+        let frequency = calibrator.frequency();
+        let beyond = if frequency > T::zero() {
+            min.checked_add(&frequency).is_none_or(|f| f > *max)
+        } else {
+            max.checked_add(&frequency).is_none_or(|f| f < *min)
+        };
+        if beyond {
+            return Ok(None);
+        }
+    }
+}
+
+/// Find the first repeated frequency without stepping through the cycle.
+///
+/// See `first_repeats` for how. Returns `None` if no frequency is ever
+/// repeated.
+///
+/// # Panics
+///
+/// If a line isn't a frequency change, or the first repeated frequency
+/// doesn't fit in an `i32`.
+pub fn part2_analytic(input: &[&str]) -> Option<i32> {
+    part2_report(input).map(|report| {
+        i32::try_from(report.frequency).unwrap_or_else(|_| {
+            panic!(
+                "first repeated frequency {} doesn't fit in an i32",
+                report.frequency
+            )
+        })
+    })
+}
+
 #[cfg(test)]
 mod tests {
 
-    use super::{part1, part2_functional, part2_imperative};
+    use alloc::vec::Vec;
+
+    use aoc_testgen::Lcg;
+
+    use super::{
+        part1, part1_checked, part2_analytic, part2_checked, part2_functional, part2_imperative,
+        Error,
+    };
 
     #[test]
     fn test_part1() {
@@ -50,12 +163,28 @@ mod tests {
         assert_eq!(part1(&["-1", "-2", "-3"]), -6);
     }
 
+    #[test]
+    #[should_panic(expected = "frequency overflowed after 2 changes")]
+    fn test_part1_overflow() {
+        part1(&["+2147483647", "+1"]);
+    }
+
     #[test]
     fn test_part2_functional() {
-        assert_eq!(part2_functional(&["+1", "-1"]), 0);
-        assert_eq!(part2_functional(&["+3", "+3", "+4", "-2", "-4"]), 10);
-        assert_eq!(part2_functional(&["-6", "+3", "+8", "+5", "-6"]), 5);
-        assert_eq!(part2_functional(&["+7", "+7", "-2", "-7", "-4"]), 14);
+        assert_eq!(part2_functional(&["+1", "-1"]), Ok(Some(0)));
+        assert_eq!(
+            part2_functional(&["+3", "+3", "+4", "-2", "-4"]),
+            Ok(Some(10))
+        );
+        assert_eq!(
+            part2_functional(&["-6", "+3", "+8", "+5", "-6"]),
+            Ok(Some(5))
+        );
+        assert_eq!(
+            part2_functional(&["+7", "+7", "-2", "-7", "-4"]),
+            Ok(Some(14))
+        );
+        assert_eq!(part2_functional(&[]), Ok(None));
     }
 
     #[test]
@@ -65,4 +194,128 @@ mod tests {
         assert_eq!(part2_imperative(&["-6", "+3", "+8", "+5", "-6"]), 5);
         assert_eq!(part2_imperative(&["+7", "+7", "-2", "-7", "-4"]), 14);
     }
+
+    #[test]
+    fn test_part2_analytic() {
+        assert_eq!(part2_analytic(&["+1", "-1"]), Some(0));
+        assert_eq!(part2_analytic(&["+3", "+3", "+4", "-2", "-4"]), Some(10));
+        assert_eq!(part2_analytic(&["-6", "+3", "+8", "+5", "-6"]), Some(5));
+        assert_eq!(part2_analytic(&["+7", "+7", "-2", "-7", "-4"]), Some(14));
+        assert_eq!(part2_analytic(&["-1", "-2", "+1"]), Some(-3));
+    }
+
+    #[test]
+    fn test_part2_functional_errors() {
+        assert_eq!(
+            part2_functional(&["+2147483647", "+1"]),
+            Err(Error::Overflow { deltas_applied: 2 })
+        );
+        assert_eq!(
+            part2_functional(&["+1", "two"]),
+            Err(Error::Parse {
+                line: 1,
+                element: "two".into()
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "first repeated frequency 2147483648 doesn't fit")]
+    fn test_part2_analytic_overflow() {
+        // Frequencies: 0, 2147483647, 2147483648, 2147483649, 2147483648.
+        part2_analytic(&["+2147483647", "+1", "+1", "-1"]);
+    }
+
+    #[test]
+    fn test_part2_analytic_no_repeat() {
+        assert_eq!(part2_analytic(&[]), None);
+        assert_eq!(part2_analytic(&["+1"]), None);
+        assert_eq!(part2_analytic(&["+1", "+1", "+1"]), None);
+        assert_eq!(part2_analytic(&["+3", "-1"]), None);
+    }
+
+    #[test]
+    fn test_part2_analytic_matches_imperative() {
+        let mut rng = Lcg::new(12345);
+        let mut next = move || rng.below(21);
+        for _ in 0..200 {
+            let len = 1 + next() as usize;
+            let deltas: Vec<_> = (0..len)
+                .map(|_| match next() as i32 - 10 {
+                    d if d >= 0 => alloc::format!("+{}", d),
+                    d => alloc::format!("{}", d),
+                })
+                .collect();
+            let input: Vec<&str> = deltas.iter().map(|d| d.as_str()).collect();
+            let expected = part2_checked::<i32>(&input).unwrap();
+            assert_eq!(part2_analytic(&input), expected, "{:?}", input);
+            // The imperative version never terminates without a repeat.
+            if let Some(expected) = expected {
+                assert_eq!(part2_imperative(&input), expected, "{:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_part1_checked() {
+        assert_eq!(part1_checked::<i32>(&["+1", "+1", "-2"]), Ok(0));
+        let input = &["+2147483647", "+1"];
+        assert_eq!(
+            part1_checked::<i32>(input),
+            Err(Error::Overflow { deltas_applied: 2 })
+        );
+        assert_eq!(part1_checked::<i64>(input), Ok(2_147_483_648));
+        // The total fits but a running total on the way doesn't.
+        assert_eq!(
+            part1_checked::<i32>(&["+2147483647", "+1", "-2"]),
+            Err(Error::Overflow { deltas_applied: 2 })
+        );
+        assert_eq!(
+            part1_checked::<i64>(&["+1", "two"]),
+            Err(Error::Parse {
+                line: 1,
+                element: "two".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_part1_checked_i128() {
+        let input = &["+9223372036854775807", "+9223372036854775807"];
+        assert!(part1_checked::<i64>(input).is_err());
+        assert_eq!(part1_checked::<i128>(input), Ok(2 * i128::from(i64::MAX)));
+    }
+
+    #[test]
+    #[cfg(feature = "bigint")]
+    fn test_part1_checked_bigint() {
+        use num_bigint::BigInt;
+
+        let input = &["+170141183460469231731687303715884105727", "+1"];
+        assert!(part1_checked::<i128>(input).is_err());
+        assert_eq!(
+            part1_checked::<BigInt>(input),
+            Ok(BigInt::from(i128::MAX) + 1)
+        );
+    }
+
+    #[test]
+    fn test_part2_checked() {
+        assert_eq!(part2_checked::<i32>(&["+1", "-1"]), Ok(Some(0)));
+        assert_eq!(
+            part2_checked::<i64>(&["+3", "+3", "+4", "-2", "-4"]),
+            Ok(Some(10))
+        );
+        assert_eq!(part2_checked::<i64>(&["-1", "-2", "+1"]), Ok(Some(-3)));
+        assert_eq!(part2_checked::<i64>(&[]), Ok(None));
+        assert_eq!(part2_checked::<i64>(&["+1", "+1", "+1"]), Ok(None));
+        assert_eq!(part2_checked::<i64>(&["+3", "-1"]), Ok(None));
+
+        let input = &["+2147483647", "+1", "-2147483648"];
+        assert_eq!(
+            part2_checked::<i32>(input),
+            Err(Error::Overflow { deltas_applied: 2 })
+        );
+        assert_eq!(part2_checked::<i64>(input), Ok(Some(0)));
+    }
 }
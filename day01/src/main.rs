@@ -3,5 +3,6 @@ use day01;
 fn main() {
     let input: Vec<&str> = include_str!("../../input/2018/day1.txt").lines().collect();
     println!("Part 1: {}", day01::part1(&input));
-    println!("Part 2: {}", day01::part2_functional(&input));
+    let repeat = day01::part2_functional(&input).unwrap_or_else(|e| panic!("{}", e));
+    println!("Part 2: {}", repeat.expect("no frequency is repeated"));
 }
@@ -0,0 +1,327 @@
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use hashbrown::HashMap;
+
+use crate::{parse_element, Calibrator, Error, Frequency};
+
+/// Where a frequency was reached: just after applying the change at
+/// `index` during pass `pass` over the input (both zero-based).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub pass: u64,
+    pub index: usize,
+}
+
+/// Everything we know about one repeated frequency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RepeatReport {
+    pub frequency: i64,
+    /// Where the frequency was reached again.
+    pub repeated_at: Position,
+    /// Where the frequency was first reached, or `None` if it was the
+    /// starting frequency.
+    pub first_seen: Option<Position>,
+    /// The net change in frequency over one pass of the input.
+    pub drift: i64,
+    /// How many changes had been applied when it repeated.
+    pub deltas_applied: u64,
+}
+
+/// The first pass over the input, which determines every later one.
+///
+/// "Orbit" `i` is the sequence of frequencies reached after `p * n + i`
+/// changes for `p = 0, 1, 2, ...`, i.e. `prefix[i] + p * drift`.
+struct Cycle {
+    prefix: Vec<i64>,
+    drift: i64,
+    /// The earliest index reaching each distinct first-pass frequency,
+    /// sorted by (residue mod drift, frequency).
+    distinct: Vec<(i64, i64, usize)>,
+    /// For each orbit, the first pass on which it reaches a frequency
+    /// which was already seen. Every later pass repeats too.
+    first_repeat_pass: Vec<Option<u64>>,
+}
+
+impl Cycle {
+    /// Returns `Error::Parse` for a line which isn't a frequency change,
+    /// and `Error::Overflow` if the first pass takes the frequency out of
+    /// the range of an `i64`.
+    fn new(input: &[&str]) -> Result<Self, Error> {
+        let mut prefix = Vec::with_capacity(input.len());
+        let mut current: i64 = 0;
+        for (i, element) in input.iter().enumerate() {
+            prefix.push(current);
+            let delta: i64 = parse_element(i, element)?;
+            current = Frequency::checked_add(&current, &delta).ok_or(Error::Overflow {
+                deltas_applied: i + 1,
+            })?;
+        }
+        let drift = current;
+        let modulus = drift.abs();
+        let residue = |p: i64| {
+            if modulus == 0 {
+                0
+            } else {
+                p.rem_euclid(modulus)
+            }
+        };
+
+        let mut first_repeat_pass = vec![None; prefix.len()];
+        let mut earliest: HashMap<i64, usize> = HashMap::with_capacity(prefix.len());
+        for (i, &p) in prefix.iter().enumerate() {
+            if earliest.contains_key(&p) {
+                first_repeat_pass[i] = Some(0);
+            } else {
+                earliest.insert(p, i);
+            }
+        }
+        let mut distinct: Vec<(i64, i64, usize)> = earliest
+            .into_iter()
+            .map(|(p, i)| (residue(p), p, i))
+            .collect();
+        distinct.sort_unstable();
+
+        if drift == 0 {
+            // Everything comes round again on the second pass.
+            for &(_, _, i) in &distinct {
+                first_repeat_pass[i] = Some(1);
+            }
+        } else {
+            // A positive drift carries the lower frequency of each pair
+            // up to the higher one; a negative drift carries the higher
+            // one down.
+            for pair in distinct.windows(2).filter(|pair| pair[0].0 == pair[1].0) {
+                let (low, high) = (pair[0], pair[1]);
+                let from = if drift > 0 { low.2 } else { high.2 };
+                first_repeat_pass[from] = Some(((high.1 - low.1) / modulus) as u64);
+            }
+        }
+
+        Ok(Cycle {
+            prefix,
+            drift,
+            distinct,
+            first_repeat_pass,
+        })
+    }
+
+    fn n(&self) -> u64 {
+        self.prefix.len() as u64
+    }
+
+    fn position(&self, deltas_applied: u64) -> Option<Position> {
+        if deltas_applied == 0 {
+            None
+        } else {
+            Some(Position {
+                pass: (deltas_applied - 1) / self.n(),
+                index: ((deltas_applied - 1) % self.n()) as usize,
+            })
+        }
+    }
+
+    /// How many changes had been applied when `frequency` was first
+    /// reached.
+    ///
+    /// `frequency` must be reachable, i.e. on one of the orbits.
+    fn first_seen(&self, frequency: i64) -> u64 {
+        let modulus = self.drift.abs();
+        if modulus == 0 {
+            let i = self.distinct.binary_search_by_key(&frequency, |d| d.1);
+            return self.distinct[i.expect("frequency must be reachable")].2 as u64;
+        }
+        // The orbit which gets there in the fewest passes is the one which
+        // starts closest to it on the side the drift comes from.
+        let key = (frequency.rem_euclid(modulus), frequency);
+        let (_, start, i) = match self.distinct.binary_search_by_key(&key, |d| (d.0, d.1)) {
+            Ok(found) => self.distinct[found],
+            Err(insert_at) if self.drift > 0 => self.distinct[insert_at - 1],
+            Err(insert_at) => self.distinct[insert_at],
+        };
+        ((frequency - start) / self.drift) as u64 * self.n() + i as u64
+    }
+
+    fn report(&self, pass: u64, i: usize) -> RepeatReport {
+        let frequency = i64::try_from(pass)
+            .ok()
+            .and_then(|pass| pass.checked_mul(self.drift))
+            .and_then(|drifted| drifted.checked_add(self.prefix[i]))
+            .expect("frequency overflowed");
+        let deltas_applied = pass * self.n() + i as u64;
+        RepeatReport {
+            frequency,
+            repeated_at: self.position(deltas_applied).unwrap(),
+            first_seen: self.position(self.first_seen(frequency)),
+            drift: self.drift,
+            deltas_applied,
+        }
+    }
+}
+
+/// The first `k` times, in order, that a frequency is reached which had
+/// already been reached before.
+///
+/// Returns fewer than `k` only if no frequency ever repeats, in which
+/// case the result is empty. Runs in `O(n log n + k log n)` without
+/// stepping through the passes in between.
+///
+/// # Panics
+///
+/// If a line isn't a frequency change, or a frequency up to the `k`th
+/// repeat doesn't fit in an `i64`.
+pub fn first_repeats(input: &[&str], k: usize) -> Vec<RepeatReport> {
+    let mut reports = Vec::with_capacity(k);
+    if input.is_empty() || k == 0 {
+        return reports;
+    }
+    let cycle = Cycle::new(input).unwrap_or_else(|e| panic!("{}", e));
+    let mut pending: Vec<(u64, usize)> = cycle
+        .first_repeat_pass
+        .iter()
+        .enumerate()
+        .filter_map(|(i, pass)| pass.map(|p| (p, i)))
+        .collect();
+    pending.sort_unstable();
+    let mut pending = pending.into_iter().peekable();
+
+    // Once an orbit starts repeating it repeats on every later pass, so
+    // walk the passes from the first repeat onwards, emitting every
+    // active orbit in index order.
+    let mut active = BTreeSet::new();
+    let mut pass = match pending.peek() {
+        Some(&(p, _)) => p,
+        None => return reports,
+    };
+    loop {
+        while let Some(&(p, i)) = pending.peek() {
+            if p != pass {
+                break;
+            }
+            active.insert(i);
+            pending.next();
+        }
+        for &i in &active {
+            reports.push(cycle.report(pass, i));
+            if reports.len() == k {
+                return reports;
+            }
+        }
+        pass += 1;
+    }
+}
+
+/// Diagnostics for the first repeated frequency, or `None` if no
+/// frequency ever repeats.
+pub fn part2_report(input: &[&str]) -> Option<RepeatReport> {
+    first_repeats(input, 1).pop()
+}
+
+/// How many times each frequency is reached over `passes` full passes of
+/// the input, counting the starting frequency once.
+pub fn visit_counts(input: &[&str], passes: usize) -> HashMap<i32, u32> {
+    let mut calibrator = Calibrator::new();
+    for _ in 0..passes {
+        for element in input {
+            calibrator.push_str(element);
+        }
+    }
+    calibrator.visit_counts().collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::{first_repeats, part2_report, visit_counts, Position, RepeatReport};
+
+    #[test]
+    fn test_part2_report() {
+        // Frequencies: 0, 3, 6, 10, 8, 4, 7, 10.
+        assert_eq!(
+            part2_report(&["+3", "+3", "+4", "-2", "-4"]),
+            Some(RepeatReport {
+                frequency: 10,
+                repeated_at: Position { pass: 1, index: 1 },
+                first_seen: Some(Position { pass: 0, index: 2 }),
+                drift: 4,
+                deltas_applied: 7,
+            })
+        );
+        assert_eq!(
+            part2_report(&["+1", "-1"]),
+            Some(RepeatReport {
+                frequency: 0,
+                repeated_at: Position { pass: 0, index: 1 },
+                first_seen: None,
+                drift: 0,
+                deltas_applied: 2,
+            })
+        );
+        assert_eq!(part2_report(&["+1", "+1"]), None);
+    }
+
+    #[test]
+    fn test_first_repeats() {
+        // Frequencies: 0, -1, -3, -2, -3, -5, -4, -5, -7, -6, -7, ...
+        let repeats = first_repeats(&["-1", "-2", "+1"], 3);
+        let summary: Vec<_> = repeats
+            .iter()
+            .map(|r| (r.frequency, r.deltas_applied, r.first_seen))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (-3, 4, Some(Position { pass: 0, index: 1 })),
+                (-5, 7, Some(Position { pass: 1, index: 1 })),
+                (-7, 10, Some(Position { pass: 2, index: 1 })),
+            ]
+        );
+        assert!(first_repeats(&["+1", "+1"], 3).is_empty());
+    }
+
+    #[test]
+    fn test_first_repeats_match_simulation() {
+        let input = &["+7", "+7", "-2", "-7", "-4", "+3", "-1"];
+        let repeats = first_repeats(input, 50);
+        let mut seen = hashbrown::HashMap::new();
+        seen.insert(0, 0);
+        let mut frequency = 0;
+        let mut expected = Vec::new();
+        for (step, element) in input.iter().cycle().enumerate() {
+            frequency += element.parse::<i64>().unwrap();
+            let step = step as u64 + 1;
+            match seen.get(&frequency) {
+                Some(&first) => expected.push((frequency, first, step)),
+                None => {
+                    seen.insert(frequency, step);
+                }
+            }
+            if expected.len() == 50 {
+                break;
+            }
+        }
+        let actual: Vec<_> = repeats
+            .iter()
+            .map(|r| {
+                let first = r
+                    .first_seen
+                    .map_or(0, |p| p.pass * input.len() as u64 + p.index as u64 + 1);
+                (r.frequency, first, r.deltas_applied)
+            })
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_visit_counts() {
+        let counts = visit_counts(&["+1", "-1"], 2);
+        assert_eq!(counts[&0], 3);
+        assert_eq!(counts[&1], 2);
+        assert_eq!(counts.len(), 2);
+    }
+}
@@ -0,0 +1,55 @@
+use aoc_testgen::Lcg;
+use criterion::{criterion_group, criterion_main, Benchmark, Criterion};
+use day02::{clusters, part2, Letters, Metric};
+
+/// `n` random 26-letter IDs, with the last one copied from the first
+/// and one letter changed.
+fn generate(n: usize) -> Vec<String> {
+    let mut rng = Lcg::new(12345);
+    let mut next = move || rng.below(26);
+    let mut ids: Vec<String> = (0..n - 1)
+        .map(|_| (0..26).map(|_| (b'a' + next() as u8) as char).collect())
+        .collect();
+    let mut planted = ids[0].clone().into_bytes();
+    planted[13] = if planted[13] == b'z' {
+        b'a'
+    } else {
+        planted[13] + 1
+    };
+    ids.push(String::from_utf8(planted).unwrap());
+    ids
+}
+
+fn part2_100k_benchmark(c: &mut Criterion) {
+    let ids = generate(100_000);
+    c.bench(
+        "part2_100k",
+        Benchmark::new("part2_100k", move |b| {
+            let input: Vec<&str> = ids.iter().map(String::as_str).collect();
+            b.iter(|| part2(&input))
+        })
+        .sample_size(10),
+    );
+}
+
+fn clusters_2k_benchmark(c: &mut Criterion) {
+    let ids = generate(2_000);
+    c.bench(
+        "clusters_2k",
+        Benchmark::new("hamming", {
+            let ids = ids.clone();
+            move |b| {
+                let input: Vec<&str> = ids.iter().map(String::as_str).collect();
+                b.iter(|| clusters(&input, Letters::Chars, Metric::Hamming, 2).unwrap())
+            }
+        })
+        .with_function("levenshtein", move |b| {
+            let input: Vec<&str> = ids.iter().map(String::as_str).collect();
+            b.iter(|| clusters(&input, Letters::Chars, Metric::Levenshtein, 2).unwrap())
+        })
+        .sample_size(10),
+    );
+}
+
+criterion_group!(benches, part2_100k_benchmark, clusters_2k_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,432 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::{is_ascii, split, Error, Letter, Letters};
+
+/// How far apart two IDs are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// The number of positions at which the IDs differ, plus the
+    /// difference in their lengths.
+    Hamming,
+    /// The fewest single-letter insertions, deletions and substitutions
+    /// which turn one ID into the other.
+    Levenshtein,
+}
+
+impl Metric {
+    fn distance<L: Eq>(self, a: &[L], b: &[L]) -> usize {
+        match self {
+            Metric::Hamming => {
+                let differing = a.iter().zip(b).filter(|(x, y)| x != y).count();
+                differing + a.len().max(b.len()) - a.len().min(b.len())
+            }
+            Metric::Levenshtein => levenshtein(a, b),
+        }
+    }
+}
+
+fn levenshtein<L: Eq>(a: &[L], b: &[L]) -> usize {
+    // One row of the usual table at a time: `row[j]` is the distance
+    // between what we've seen of `a` and the first `j` letters of `b`.
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn longest_common_subsequence<L: Letter>(a: &[L], b: &[L]) -> Vec<L> {
+    // `table[i * width + j]` is the length of the longest common
+    // subsequence of `a[i..]` and `b[j..]`.
+    let width = b.len() + 1;
+    let mut table = vec![0; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i * width + j] = if a[i] == b[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+    let mut common = Vec::with_capacity(table[0]);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            common.push(a[i]);
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    common
+}
+
+struct Node {
+    id: usize,
+    /// Each child with its distance from this node's ID.
+    children: Vec<(usize, usize)>,
+}
+
+/// A BK-tree over some of the IDs.
+///
+/// Every child is filed under its distance from its parent, so by the
+/// triangle inequality a search for IDs within `k` of a query at
+/// distance `d` from a node only needs the children filed under
+/// `d - k..=d + k`.
+struct BkTree<'a, L> {
+    metric: Metric,
+    ids: &'a [&'a [L]],
+    nodes: Vec<Node>,
+}
+
+impl<'a, L: Eq> BkTree<'a, L> {
+    fn new(metric: Metric, ids: &'a [&'a [L]]) -> Self {
+        BkTree {
+            metric,
+            ids,
+            nodes: Vec::with_capacity(ids.len()),
+        }
+    }
+
+    fn distance(&self, a: usize, b: usize) -> usize {
+        self.metric.distance(self.ids[a], self.ids[b])
+    }
+
+    fn insert(&mut self, id: usize) {
+        let new = self.nodes.len();
+        self.nodes.push(Node {
+            id,
+            children: Vec::new(),
+        });
+        if new == 0 {
+            return;
+        }
+        let mut node = 0;
+        loop {
+            let d = self.distance(self.nodes[node].id, id);
+            match self.nodes[node].children.iter().find(|c| c.0 == d) {
+                Some(&(_, child)) => node = child,
+                None => {
+                    self.nodes[node].children.push((d, new));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Call `f` with every ID in the tree within `k` of `id`, and the
+    /// distance to it.
+    fn within(&self, id: usize, k: usize, mut f: impl FnMut(usize, usize)) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let d = self.distance(node.id, id);
+            if d <= k {
+                f(node.id, d);
+            }
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|&&(from_parent, _)| from_parent + k >= d && from_parent <= d + k)
+                    .map(|&(_, child)| child),
+            );
+        }
+    }
+}
+
+/// Two IDs within the distance searched for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimilarPair {
+    /// The index of the earlier ID in the input.
+    pub first: usize,
+    /// The index of the later ID in the input.
+    pub second: usize,
+    pub distance: usize,
+}
+
+/// IDs which are linked by a chain of similar pairs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cluster {
+    /// Indices in the input, in order.
+    pub ids: Vec<usize>,
+    /// Letters which appear, in order, in every ID of the cluster.
+    ///
+    /// For a pair this is their longest common subsequence. Larger
+    /// clusters fold that over their IDs in order, which may not find the
+    /// longest one common to all of them.
+    pub common: String,
+}
+
+fn pairs<L: Eq>(ids: &[&[L]], metric: Metric, k: usize) -> Vec<SimilarPair> {
+    let mut tree = BkTree::new(metric, ids);
+    let mut pairs = Vec::new();
+    for second in 0..ids.len() {
+        let start = pairs.len();
+        tree.within(second, k, |first, distance| {
+            pairs.push(SimilarPair {
+                first,
+                second,
+                distance,
+            })
+        });
+        pairs[start..].sort_unstable_by_key(|pair| pair.first);
+        tree.insert(second);
+    }
+    pairs
+}
+
+fn find_root(parents: &mut [usize], mut id: usize) -> usize {
+    while parents[id] != id {
+        parents[id] = parents[parents[id]];
+        id = parents[id];
+    }
+    id
+}
+
+fn group<L: Letter>(ids: &[&[L]], metric: Metric, k: usize) -> Vec<Cluster> {
+    let mut parents: Vec<usize> = (0..ids.len()).collect();
+    for pair in pairs(ids, metric, k) {
+        let (a, b) = (
+            find_root(&mut parents, pair.first),
+            find_root(&mut parents, pair.second),
+        );
+        // Root each cluster at its first ID, so they come out in order.
+        parents[a.max(b)] = a.min(b);
+    }
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); ids.len()];
+    for id in 0..ids.len() {
+        let root = find_root(&mut parents, id);
+        members[root].push(id);
+    }
+    members
+        .into_iter()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let common = members[1..]
+                .iter()
+                .fold(ids[members[0]].to_vec(), |common, &id| {
+                    longest_common_subsequence(&common, ids[id])
+                });
+            let mut s = String::new();
+            common.into_iter().for_each(|letter| letter.push_to(&mut s));
+            Cluster {
+                ids: members,
+                common: s,
+            }
+        })
+        .collect()
+}
+
+fn check_not_empty(input: &[&str]) -> Result<(), Error> {
+    match input.iter().position(|id| id.is_empty()) {
+        Some(index) => Err(Error::EmptyId { index }),
+        None => Ok(()),
+    }
+}
+
+/// Every pair of IDs at most `k` apart, ordered by the later ID and then
+/// the earlier one.
+///
+/// The IDs go into a BK-tree one by one, each first looking up the ones
+/// before it, so only the parts of the tree which could hold a match are
+/// searched. That pays off when IDs vary in how far apart they are; if
+/// they're all about equally far apart, as random IDs are, there's
+/// little to skip and it's no better than comparing every pair.
+pub fn similar_pairs(
+    input: &[&str],
+    letters: Letters,
+    metric: Metric,
+    k: usize,
+) -> Result<Vec<SimilarPair>, Error> {
+    check_not_empty(input)?;
+    if input.iter().all(|id| is_ascii(id, letters)) {
+        let ids: Vec<&[u8]> = input.iter().map(|id| id.as_bytes()).collect();
+        return Ok(pairs(&ids, metric, k));
+    }
+    let split: Vec<Vec<&str>> = input.iter().map(|id| split(id, letters)).collect();
+    let ids: Vec<&[&str]> = split.iter().map(Vec::as_slice).collect();
+    Ok(pairs(&ids, metric, k))
+}
+
+/// Group the IDs which are linked by pairs at most `k` apart.
+///
+/// IDs with nothing within `k` of them are left out, and clusters are
+/// ordered by their first ID.
+pub fn clusters(
+    input: &[&str],
+    letters: Letters,
+    metric: Metric,
+    k: usize,
+) -> Result<Vec<Cluster>, Error> {
+    check_not_empty(input)?;
+    if input.iter().all(|id| is_ascii(id, letters)) {
+        let ids: Vec<&[u8]> = input.iter().map(|id| id.as_bytes()).collect();
+        return Ok(group(&ids, metric, k));
+    }
+    let split: Vec<Vec<&str>> = input.iter().map(|id| split(id, letters)).collect();
+    let ids: Vec<&[&str]> = split.iter().map(Vec::as_slice).collect();
+    Ok(group(&ids, metric, k))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use aoc_testgen::Lcg;
+
+    use super::{clusters, levenshtein, similar_pairs, Cluster, Metric, SimilarPair};
+    use crate::{Error, Letters};
+
+    fn pair(first: usize, second: usize, distance: usize) -> SimilarPair {
+        SimilarPair {
+            first,
+            second,
+            distance,
+        }
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein(b"kitten", b"sitting"), 3);
+        assert_eq!(levenshtein(b"", b"abc"), 3);
+        assert_eq!(levenshtein(b"abc", b""), 3);
+        assert_eq!(levenshtein(b"flaw", b"lawn"), 2);
+        assert_eq!(levenshtein(b"same", b"same"), 0);
+    }
+
+    #[test]
+    fn test_similar_pairs() {
+        let input = &[
+            "abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz",
+        ];
+        assert_eq!(
+            similar_pairs(input, Letters::Chars, Metric::Hamming, 1),
+            Ok(vec![pair(1, 4, 1)])
+        );
+        assert_eq!(
+            similar_pairs(input, Letters::Chars, Metric::Hamming, 2),
+            Ok(vec![pair(1, 4, 1), pair(0, 5, 2)])
+        );
+        // Hamming counts the extra letters of a longer ID.
+        assert_eq!(
+            similar_pairs(&["abc", "abcd", "xbcd"], Letters::Chars, Metric::Hamming, 1),
+            Ok(vec![pair(0, 1, 1), pair(1, 2, 1)])
+        );
+        assert_eq!(
+            similar_pairs(&["abc", "abc"], Letters::Chars, Metric::Hamming, 0),
+            Ok(vec![pair(0, 1, 0)])
+        );
+        assert_eq!(
+            similar_pairs(&["abc", ""], Letters::Chars, Metric::Hamming, 1),
+            Err(Error::EmptyId { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_similar_pairs_levenshtein() {
+        let input = &["kitten", "sitting", "mitten", "fitting"];
+        assert_eq!(
+            similar_pairs(input, Letters::Chars, Metric::Levenshtein, 1),
+            Ok(vec![pair(0, 2, 1), pair(1, 3, 1)])
+        );
+        assert_eq!(
+            similar_pairs(input, Letters::Chars, Metric::Levenshtein, 3)
+                .unwrap()
+                .len(),
+            6
+        );
+        // One letter of a non-ASCII ID is one edit.
+        assert_eq!(
+            similar_pairs(
+                &["naïve", "naive", "nave"],
+                Letters::Chars,
+                Metric::Levenshtein,
+                1
+            ),
+            Ok(vec![pair(0, 1, 1), pair(0, 2, 1), pair(1, 2, 1)])
+        );
+    }
+
+    #[test]
+    fn test_clusters() {
+        let input = &["kitten", "sitting", "mitten", "fitting", "zzz"];
+        assert_eq!(
+            clusters(input, Letters::Chars, Metric::Levenshtein, 1),
+            Ok(vec![
+                Cluster {
+                    ids: vec![0, 2],
+                    common: "itten".into()
+                },
+                Cluster {
+                    ids: vec![1, 3],
+                    common: "itting".into()
+                },
+            ])
+        );
+        // Chained through "mitten" and "sitting".
+        assert_eq!(
+            clusters(input, Letters::Chars, Metric::Levenshtein, 3),
+            Ok(vec![Cluster {
+                ids: vec![0, 1, 2, 3],
+                common: "ittn".into()
+            }])
+        );
+        assert_eq!(
+            clusters(&["日本語", "日本人"], Letters::Chars, Metric::Hamming, 1),
+            Ok(vec![Cluster {
+                ids: vec![0, 1],
+                common: "日本".into()
+            }])
+        );
+    }
+
+    #[test]
+    fn test_similar_pairs_match_brute_force() {
+        let mut rng = Lcg::new(12345);
+        let mut next = move |n: u32| rng.below(n);
+        for metric in [Metric::Hamming, Metric::Levenshtein] {
+            for _ in 0..50 {
+                let ids: Vec<String> = (0..30)
+                    .map(|_| {
+                        let len = 3 + next(3);
+                        (0..len).map(|_| (b'a' + next(3) as u8) as char).collect()
+                    })
+                    .collect();
+                let input: Vec<&str> = ids.iter().map(String::as_str).collect();
+                let k = next(3) as usize;
+                let mut expected = Vec::new();
+                for second in 0..input.len() {
+                    for first in 0..second {
+                        let distance =
+                            metric.distance(input[first].as_bytes(), input[second].as_bytes());
+                        if distance <= k {
+                            expected.push(pair(first, second, distance));
+                        }
+                    }
+                }
+                assert_eq!(
+                    similar_pairs(&input, Letters::Chars, metric, k),
+                    Ok(expected),
+                    "{:?}",
+                    input
+                );
+            }
+        }
+    }
+}
@@ -1,95 +1,472 @@
-enum Candidate {
-    None,
-    Two,
-    Three,
-    Both,
-}
-
-fn check_word(word: &&str) -> Candidate {
-    // Use an array of counts rather than having to do hash lookups
-    // or anything silly, since we know IDs are all lower case letters
-    let initial = [0; 26];
-    let counts = word.chars().fold(initial, |mut acc, c| {
-        // 'a' is 97 in ascii, so subtracting 97 gets us its index (zero)
-        // Similarly, this gets us an index of 25 for 'z'.
-        let index: usize = c as usize - 97;
-        acc[index] += 1;
-        acc
-    });
-    let any_threes = counts.iter().any(|&x| x == 3);
-    let any_twos = counts.iter().any(|&x| x == 2);
-    match (any_twos, any_threes) {
-        (true, true) => Candidate::Both,
-        (true, false) => Candidate::Two,
-        (false, true) => Candidate::Three,
-        (false, false) => Candidate::None,
-    }
-}
-
-#[derive(Default)]
-struct Counts {
-    two: u64,
-    three: u64,
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use hashbrown::HashMap;
+#[cfg(feature = "graphemes")]
+use unicode_segmentation::UnicodeSegmentation;
+
+mod cluster;
+
+pub use cluster::{clusters, similar_pairs, Cluster, Metric, SimilarPair};
+
+/// What counts as one letter of an ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Letters {
+    /// Each `char` is a letter.
+    Chars,
+    /// Each extended grapheme cluster is a letter, so e.g. `e` followed by
+    /// a combining accent is one letter rather than two.
+    #[cfg(feature = "graphemes")]
+    Graphemes,
 }
 
-pub fn part1(input: &[&str]) -> u64 {
-    let mut counts = Counts::default();
-    for candidate in input.iter().map(check_word) {
-        match candidate {
-            Candidate::Both => {
-                counts.two += 1;
-                counts.three += 1;
-            }
-            Candidate::Three => counts.three += 1,
-            Candidate::Two => counts.two += 1,
-            _ => {}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The ID at `index` has no letters.
+    EmptyId { index: usize },
+    /// The ID at `index` has `found` letters, but the first ID has
+    /// `expected`.
+    LengthMismatch {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// No two IDs differ at exactly one position.
+    NoMatch,
+    /// `pairs` pairs of IDs differ at exactly one position, so there's no
+    /// single answer.
+    Ambiguous { pairs: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EmptyId { index } => write!(f, "box ID {} is empty", index + 1),
+            Error::LengthMismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "box ID {} has {} letters, but the first has {}",
+                index + 1,
+                found,
+                expected
+            ),
+            Error::NoMatch => write!(f, "no two box IDs differ at exactly one position"),
+            Error::Ambiguous { pairs } => write!(
+                f,
+                "{} pairs of box IDs differ at exactly one position",
+                pairs
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Whether every letter of `id` is a single byte, whichever way letters
+/// are counted.
+fn is_ascii(id: &str, letters: Letters) -> bool {
+    match letters {
+        Letters::Chars => id.is_ascii(),
+        // CRLF is the one ASCII grapheme cluster with two chars.
+        #[cfg(feature = "graphemes")]
+        Letters::Graphemes => id.is_ascii() && !id.contains("\r\n"),
+    }
+}
+
+/// Split `id` into its letters.
+fn split(id: &str, letters: Letters) -> Vec<&str> {
+    match letters {
+        Letters::Chars => id
+            .char_indices()
+            .map(|(i, c)| &id[i..i + c.len_utf8()])
+            .collect(),
+        #[cfg(feature = "graphemes")]
+        Letters::Graphemes => id.graphemes(true).collect(),
+    }
+}
+
+/// Call `f` with the number of times each distinct letter of `word`
+/// appears.
+fn for_each_letter_count(word: &str, letters: Letters, mut f: impl FnMut(usize)) {
+    if word.bytes().all(|b| b.is_ascii_lowercase()) {
+        // Use an array of counts rather than having to do hash lookups
+        // or anything silly, since these are all lower case letters
+        let counts = word.bytes().fold([0; 26], |mut acc, b| {
+            acc[usize::from(b - b'a')] += 1;
+            acc
+        });
+        counts.iter().filter(|&&n| n > 0).for_each(|&n| f(n));
+        return;
+    }
+    // Otherwise sort the letters so equal ones are next to each other,
+    // and measure the runs.
+    let mut sorted = split(word, letters);
+    sorted.sort_unstable();
+    let mut run = 0;
+    for (i, letter) in sorted.iter().enumerate() {
+        run += 1;
+        if sorted.get(i + 1) != Some(letter) {
+            f(run);
+            run = 0;
         }
     }
-    counts.two * counts.three
 }
 
-enum StrDiff {
-    One(usize),
-    More,
+/// The IDs which have some letter appearing exactly `times` times.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Multiplicity {
+    pub times: usize,
+    /// How many IDs there are, i.e. `ids.len()`.
+    pub count: u64,
+    /// Their indices in the input, in order.
+    pub ids: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChecksumReport {
+    /// One entry per requested multiplicity, in the order requested.
+    pub multiplicities: Vec<Multiplicity>,
+    /// The counts, combined.
+    pub checksum: u64,
 }
 
-fn str_diff(a: &str, b: &str) -> StrDiff {
-    let diffs: Vec<usize> = a
-        .chars()
-        .enumerate()
-        .zip(b.chars())
-        .filter(|((_, a), b)| a != b)
-        .map(|((i, _), _)| i)
+/// Count the IDs with some letter appearing exactly `m` times, for each
+/// `m` in `multiplicities`, and combine the counts into a checksum.
+///
+/// `combine` is given the counts in the same order as `multiplicities`.
+/// An ID can count towards several multiplicities, but only once towards
+/// each.
+pub fn checksum_report(
+    input: &[&str],
+    letters: Letters,
+    multiplicities: &[usize],
+    combine: impl FnOnce(&[u64]) -> u64,
+) -> Result<ChecksumReport, Error> {
+    let mut report: Vec<Multiplicity> = multiplicities
+        .iter()
+        .map(|&times| Multiplicity {
+            times,
+            count: 0,
+            ids: Vec::new(),
+        })
         .collect();
-    match diffs.len() {
-        1 => StrDiff::One(diffs[0]),
-        _ => StrDiff::More,
+    let mut present = vec![false; multiplicities.len()];
+    for (index, word) in input.iter().enumerate() {
+        if word.is_empty() {
+            return Err(Error::EmptyId { index });
+        }
+        present.iter_mut().for_each(|p| *p = false);
+        for_each_letter_count(word, letters, |n| {
+            for (p, &times) in present.iter_mut().zip(multiplicities) {
+                *p |= n == times;
+            }
+        });
+        for (multiplicity, _) in report.iter_mut().zip(&present).filter(|(_, &p)| p) {
+            multiplicity.count += 1;
+            multiplicity.ids.push(index);
+        }
     }
+    let counts: Vec<u64> = report.iter().map(|m| m.count).collect();
+    Ok(ChecksumReport {
+        checksum: combine(&counts),
+        multiplicities: report,
+    })
 }
 
-pub fn part2(input: &[&str]) -> String {
-    for s1 in input.iter() {
-        for s2 in input.iter().skip(1) {
-            match str_diff(s1, s2) {
-                StrDiff::One(idx) => {
-                    return s1
-                        .chars()
-                        .enumerate()
-                        .filter(|(i, _)| i != &idx)
-                        .map(|(_, c)| c)
-                        .collect();
+/// The number of IDs with a letter appearing exactly twice, times the
+/// number with a letter appearing exactly three times.
+pub fn checksum(input: &[&str], letters: Letters) -> Result<u64, Error> {
+    checksum_report(input, letters, &[2, 3], |counts| counts.iter().product())
+        .map(|report| report.checksum)
+}
+
+pub fn part1(input: &[&str]) -> u64 {
+    checksum(input, Letters::Chars).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Any odd multiplier will do; this is the 64-bit FNV prime.
+const BASE: u64 = 0x0000_0100_0000_01b3;
+
+/// One letter of an ID: a byte when every ID is ASCII, and a slice of
+/// the ID otherwise.
+trait Letter: Copy + Eq + Hash {
+    /// The letter's term in the polynomial hash of an ID.
+    fn value(self) -> u64;
+
+    fn push_to(self, s: &mut String);
+}
+
+impl Letter for u8 {
+    fn value(self) -> u64 {
+        u64::from(self)
+    }
+
+    fn push_to(self, s: &mut String) {
+        s.push(char::from(self));
+    }
+}
+
+impl Letter for &str {
+    fn value(self) -> u64 {
+        // FNV-1a, so multi-byte letters still get a single term.
+        self.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(BASE)
+        })
+    }
+
+    fn push_to(self, s: &mut String) {
+        s.push_str(self);
+    }
+}
+
+/// An ID with the letter at `skip` left out.
+///
+/// `hash` is the polynomial hash of the whole ID with that letter's term
+/// subtracted, so two masked IDs hash the same if they're equal, without
+/// rehashing the rest of the ID for every `skip`.
+struct Masked<'a, L> {
+    id: &'a [L],
+    skip: usize,
+    hash: u64,
+}
+
+impl<L> Hash for Masked<'_, L> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl<L: Eq> PartialEq for Masked<'_, L> {
+    fn eq(&self, other: &Self) -> bool {
+        let skip = self.skip;
+        skip == other.skip
+            && self.id[..skip] == other.id[..skip]
+            && self.id[skip + 1..] == other.id[skip + 1..]
+    }
+}
+
+impl<L: Eq> Eq for Masked<'_, L> {}
+
+fn polynomial_hash<L: Letter>(id: &[L]) -> u64 {
+    id.iter()
+        .fold(0, |h, &l| h.wrapping_mul(BASE).wrapping_add(l.value()))
+}
+
+/// Check every ID has the same number of letters, and return it.
+fn common_len<L>(ids: &[&[L]]) -> Result<usize, Error> {
+    let expected = ids.first().map_or(0, |id| id.len());
+    for (index, id) in ids.iter().enumerate() {
+        if id.is_empty() {
+            return Err(Error::EmptyId { index });
+        }
+        if id.len() != expected {
+            return Err(Error::LengthMismatch {
+                index,
+                expected,
+                found: id.len(),
+            });
+        }
+    }
+    Ok(expected)
+}
+
+/// Call `f` with every pair of IDs which differ at exactly one position:
+/// the index of the earlier ID, then the later one, then the position.
+/// Stops early if `f` returns `false`.
+///
+/// For each position in turn, every distinct ID goes into a hash map with
+/// that position masked out, and IDs which collide are a pair. That's
+/// `O(n * L)` for `n` IDs of length `L`, plus the pairs themselves, rather
+/// than comparing every pair. Copies of an ID are set aside first and
+/// paired with whatever the first copy pairs with, since two distinct IDs
+/// which collide always differ at the masked position, but copies would
+/// collide without being a pair.
+fn for_each_one_off<L: Letter>(
+    ids: &[&[L]],
+    mut f: impl FnMut(usize, usize, usize) -> bool,
+) -> Result<(), Error> {
+    let len = common_len(ids)?;
+    // The first copy of each distinct ID, and for each copy the next one.
+    let mut distinct = Vec::with_capacity(ids.len());
+    let mut next_copy: Vec<Option<usize>> = vec![None; ids.len()];
+    let mut last_copy: HashMap<&[L], usize> = HashMap::with_capacity(ids.len());
+    for (i, &id) in ids.iter().enumerate() {
+        match last_copy.insert(id, i) {
+            Some(last) => next_copy[last] = Some(i),
+            None => distinct.push(i),
+        }
+    }
+    let copies = |first: usize| core::iter::successors(Some(first), |&i| next_copy[i]);
+
+    let mut powers = Vec::with_capacity(len);
+    let mut power: u64 = 1;
+    for _ in 0..len {
+        powers.push(power);
+        power = power.wrapping_mul(BASE);
+    }
+    let hashes: Vec<u64> = ids.iter().map(|id| polynomial_hash(id)).collect();
+
+    // The latest distinct ID with each masked form, and for each one the one
+    // before it with the same masked form.
+    let mut latest: HashMap<Masked<L>, usize> = HashMap::with_capacity(ids.len());
+    let mut previous: Vec<Option<usize>> = vec![None; ids.len()];
+    for skip in 0..len {
+        latest.clear();
+        for &i in &distinct {
+            let id = ids[i];
+            let term = id[skip].value().wrapping_mul(powers[len - 1 - skip]);
+            let masked = Masked {
+                id,
+                skip,
+                hash: hashes[i].wrapping_sub(term),
+            };
+            previous[i] = latest.insert(masked, i);
+            let mut other = previous[i];
+            while let Some(j) = other {
+                for a in copies(j) {
+                    for b in copies(i) {
+                        if !f(a.min(b), a.max(b), skip) {
+                            return Ok(());
+                        }
+                    }
                 }
-                StrDiff::More => {}
+                other = previous[j];
             }
         }
     }
-    unreachable!()
+    Ok(())
+}
+
+fn without_letter<L: Letter>(id: &[L], skip: usize) -> String {
+    let mut common = String::new();
+    for (i, letter) in id.iter().enumerate() {
+        if i != skip {
+            letter.push_to(&mut common);
+        }
+    }
+    common
+}
+
+/// The letters shared by the first two IDs found which differ at exactly
+/// one position, or `None` if there's no such pair.
+///
+/// Every ID must have the same, non-zero, number of letters.
+pub fn common_letters(input: &[&str], letters: Letters) -> Result<Option<String>, Error> {
+    let mut common = None;
+    if input.iter().all(|id| is_ascii(id, letters)) {
+        let ids: Vec<&[u8]> = input.iter().map(|id| id.as_bytes()).collect();
+        for_each_one_off(&ids, |first, _, skip| {
+            common = Some(without_letter(ids[first], skip));
+            false
+        })?;
+    } else {
+        let split: Vec<Vec<&str>> = input.iter().map(|id| split(id, letters)).collect();
+        let ids: Vec<&[&str]> = split.iter().map(Vec::as_slice).collect();
+        for_each_one_off(&ids, |first, _, skip| {
+            common = Some(without_letter(ids[first], skip));
+            false
+        })?;
+    }
+    Ok(common)
+}
+
+/// Two IDs which differ at exactly one position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OneOffPair<'a> {
+    /// The index of the earlier ID in the input.
+    pub first: usize,
+    /// The index of the later ID in the input.
+    pub second: usize,
+    /// Which letter they differ at.
+    pub position: usize,
+    pub first_id: &'a str,
+    pub second_id: &'a str,
+    /// The letters they share.
+    pub common: String,
+}
+
+fn collect_one_offs<'a, L: Letter>(
+    input: &[&'a str],
+    ids: &[&[L]],
+) -> Result<Vec<OneOffPair<'a>>, Error> {
+    let mut pairs = Vec::new();
+    for_each_one_off(ids, |first, second, position| {
+        pairs.push(OneOffPair {
+            first,
+            second,
+            position,
+            first_id: input[first],
+            second_id: input[second],
+            common: without_letter(ids[first], position),
+        });
+        true
+    })?;
+    Ok(pairs)
+}
+
+/// Every pair of IDs which differ at exactly one position, ordered by the
+/// earlier ID and then the later one.
+///
+/// Every ID must have the same, non-zero, number of letters.
+pub fn one_off_pairs<'a>(
+    input: &[&'a str],
+    letters: Letters,
+) -> Result<Vec<OneOffPair<'a>>, Error> {
+    let mut pairs = if input.iter().all(|id| is_ascii(id, letters)) {
+        let ids: Vec<&[u8]> = input.iter().map(|id| id.as_bytes()).collect();
+        collect_one_offs(input, &ids)?
+    } else {
+        let split: Vec<Vec<&str>> = input.iter().map(|id| split(id, letters)).collect();
+        let ids: Vec<&[&str]> = split.iter().map(Vec::as_slice).collect();
+        collect_one_offs(input, &ids)?
+    };
+    pairs.sort_unstable_by_key(|pair| (pair.first, pair.second));
+    Ok(pairs)
+}
+
+/// The only pair of IDs which differ at exactly one position.
+///
+/// Returns `Error::NoMatch` if there's no such pair and
+/// `Error::Ambiguous` if there's more than one, rather than picking one.
+pub fn unique_one_off_pair<'a>(
+    input: &[&'a str],
+    letters: Letters,
+) -> Result<OneOffPair<'a>, Error> {
+    let mut pairs = one_off_pairs(input, letters)?;
+    match pairs.len() {
+        0 => Err(Error::NoMatch),
+        1 => Ok(pairs.remove(0)),
+        n => Err(Error::Ambiguous { pairs: n }),
+    }
+}
+
+pub fn part2(input: &[&str]) -> String {
+    unique_one_off_pair(input, Letters::Chars)
+        .map(|pair| pair.common)
+        .unwrap_or_else(|e| panic!("{}", e))
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::{part1, part2};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use aoc_testgen::Lcg;
+
+    use super::{
+        checksum, checksum_report, common_letters, one_off_pairs, part1, part2,
+        unique_one_off_pair, Error, Letters, Multiplicity, OneOffPair,
+    };
 
     #[test]
     fn test_part1() {
@@ -106,4 +483,257 @@ mod tests {
         ];
         assert_eq!(part2(input), "fgij");
     }
+
+    #[test]
+    fn test_checksum_any_char() {
+        // The same IDs as in `test_part1`, in other alphabets.
+        let input = &[
+            "ABCDEF", "BABABC", "ABBCDE", "ABCCCD", "AABCDD", "ABCDEE", "ABABAB",
+        ];
+        assert_eq!(checksum(input, Letters::Chars), Ok(12));
+        let input = &[
+            "αβγδεζ",
+            "βαβαβγ",
+            "αββγδε",
+            "αβγγγδ",
+            "ααβγδδ",
+            "αβγδεε",
+            "αβαβαβ",
+        ];
+        assert_eq!(checksum(input, Letters::Chars), Ok(12));
+        assert_eq!(checksum(&["a1b2", "11x", "1!!!"], Letters::Chars), Ok(1));
+    }
+
+    #[test]
+    fn test_checksum_report() {
+        let input = &[
+            "abcdef", "bababc", "abbcde", "abcccd", "aabcdd", "abcdee", "ababab", "aaaabb",
+        ];
+        let report = checksum_report(input, Letters::Chars, &[2, 3, 4], |counts| {
+            counts.iter().sum()
+        })
+        .unwrap();
+        let multiplicity = |times, ids: &[usize]| Multiplicity {
+            times,
+            count: ids.len() as u64,
+            ids: ids.to_vec(),
+        };
+        assert_eq!(
+            report.multiplicities,
+            vec![
+                multiplicity(2, &[1, 2, 4, 5, 7]),
+                multiplicity(3, &[1, 3, 6]),
+                multiplicity(4, &[7]),
+            ]
+        );
+        assert_eq!(report.checksum, 9);
+
+        let report =
+            checksum_report(input, Letters::Chars, &[], |counts| counts.len() as u64).unwrap();
+        assert!(report.multiplicities.is_empty());
+        assert_eq!(report.checksum, 0);
+    }
+
+    #[test]
+    fn test_checksum_errors() {
+        assert_eq!(
+            checksum(&["abc", ""], Letters::Chars),
+            Err(Error::EmptyId { index: 1 })
+        );
+        assert_eq!(checksum(&[], Letters::Chars), Ok(0));
+    }
+
+    #[test]
+    fn test_common_letters() {
+        let letters = Letters::Chars;
+        // The pair includes the first ID.
+        assert_eq!(
+            common_letters(&["abcde", "fghij", "abxde"], letters),
+            Ok(Some("abde".into()))
+        );
+        // Differing at either end.
+        assert_eq!(
+            common_letters(&["xbc", "abc"], letters),
+            Ok(Some("bc".into()))
+        );
+        assert_eq!(
+            common_letters(&["abc", "abx"], letters),
+            Ok(Some("ab".into()))
+        );
+        // Duplicates aren't one apart.
+        assert_eq!(common_letters(&["abc", "abc", "xyz"], letters), Ok(None));
+        assert_eq!(
+            common_letters(&["abc", "abc", "abd"], letters),
+            Ok(Some("ab".into()))
+        );
+        assert_eq!(common_letters(&[], letters), Ok(None));
+        assert_eq!(common_letters(&["a"], letters), Ok(None));
+        assert_eq!(common_letters(&["a", "b"], letters), Ok(Some("".into())));
+    }
+
+    #[test]
+    fn test_common_letters_unicode() {
+        let letters = Letters::Chars;
+        assert_eq!(
+            common_letters(&["Straße", "Strasse", "Strafe"], letters),
+            Err(Error::LengthMismatch {
+                index: 1,
+                expected: 6,
+                found: 7
+            })
+        );
+        assert_eq!(
+            common_letters(&["Straße", "Strafe", "ABCDEF"], letters),
+            Ok(Some("Strae".into()))
+        );
+        // Differing in one byte of a multi-byte char.
+        assert_eq!(
+            common_letters(&["xαy", "xβy"], letters),
+            Ok(Some("xy".into()))
+        );
+        assert_eq!(
+            common_letters(&["日本語", "日本人", "中国語"], letters),
+            Ok(Some("日本".into()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "graphemes")]
+    fn test_common_letters_graphemes() {
+        // "e\u{301}" is an e with a combining acute accent: two chars but
+        // one grapheme.
+        let input = &["cafe\u{301}", "cafe"];
+        assert_eq!(
+            common_letters(input, Letters::Chars),
+            Err(Error::LengthMismatch {
+                index: 1,
+                expected: 5,
+                found: 4
+            })
+        );
+        assert_eq!(
+            common_letters(input, Letters::Graphemes),
+            Ok(Some("caf".into()))
+        );
+        assert_eq!(checksum(&["e\u{301}e\u{301}x"], Letters::Graphemes), Ok(0));
+        assert_eq!(
+            common_letters(&["ae\u{301}", "be\u{301}"], Letters::Graphemes),
+            Ok(Some("e\u{301}".into()))
+        );
+    }
+
+    #[test]
+    fn test_common_letters_errors() {
+        assert_eq!(
+            common_letters(&["abc", "", "abd"], Letters::Chars),
+            Err(Error::EmptyId { index: 1 })
+        );
+        assert_eq!(
+            common_letters(&["abc", "abcd"], Letters::Chars),
+            Err(Error::LengthMismatch {
+                index: 1,
+                expected: 3,
+                found: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_one_off_pairs() {
+        let input = &["abcd", "abxd", "abcd", "zbxd", "wxyz"];
+        let pair = |first, second, position, common: &str| OneOffPair {
+            first,
+            second,
+            position,
+            first_id: input[first],
+            second_id: input[second],
+            common: common.into(),
+        };
+        assert_eq!(
+            one_off_pairs(input, Letters::Chars),
+            Ok(vec![
+                pair(0, 1, 2, "abd"),
+                pair(1, 2, 2, "abd"),
+                pair(1, 3, 0, "bxd"),
+            ])
+        );
+        assert_eq!(
+            unique_one_off_pair(input, Letters::Chars),
+            Err(Error::Ambiguous { pairs: 3 })
+        );
+        // Every copy of an ID pairs with every copy of the other.
+        let input = &["abxd", "abcd", "abcd", "abxd", "abcd"];
+        let pairs: Vec<(usize, usize)> = one_off_pairs(input, Letters::Chars)
+            .unwrap()
+            .iter()
+            .map(|pair| (pair.first, pair.second))
+            .collect();
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (0, 4), (1, 3), (2, 3), (3, 4)]);
+        assert_eq!(
+            unique_one_off_pair(&["abcd", "wxyz"], Letters::Chars),
+            Err(Error::NoMatch)
+        );
+        assert_eq!(
+            unique_one_off_pair(&["日本語", "日本人", "中国語"], Letters::Chars),
+            Ok(OneOffPair {
+                first: 0,
+                second: 1,
+                position: 2,
+                first_id: "日本語",
+                second_id: "日本人",
+                common: "日本".into(),
+            })
+        );
+        assert_eq!(
+            one_off_pairs(&["ab", "abc"], Letters::Chars),
+            Err(Error::LengthMismatch {
+                index: 1,
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_common_letters_matches_pairwise() {
+        let mut rng = Lcg::new(12345);
+        let mut next = move || rng.below(3);
+        for round in 0..300 {
+            // Alternate between the ASCII fast path and the general one.
+            let alphabet = if round % 2 == 0 {
+                ['a', 'b', 'c']
+            } else {
+                ['α', 'b', 'ç']
+            };
+            let ids: Vec<String> = (0..6)
+                .map(|_| (0..4).map(|_| alphabet[next() as usize]).collect())
+                .collect();
+            let input: Vec<&str> = ids.iter().map(String::as_str).collect();
+            let one_off =
+                |a: &str, b: &str| a.chars().zip(b.chars()).filter(|(x, y)| x != y).count() == 1;
+            let expected: Vec<String> = input
+                .iter()
+                .enumerate()
+                .flat_map(|(i, a)| input[i + 1..].iter().map(move |b| (*a, *b)))
+                .filter(|(a, b)| one_off(a, b))
+                .map(|(a, b)| {
+                    a.chars()
+                        .zip(b.chars())
+                        .filter(|(x, y)| x == y)
+                        .map(|(x, _)| x)
+                        .collect()
+                })
+                .collect();
+            match common_letters(&input, Letters::Chars).unwrap() {
+                Some(common) => assert!(expected.contains(&common), "{:?}", input),
+                None => assert!(expected.is_empty(), "{:?}", input),
+            }
+            let all: Vec<String> = one_off_pairs(&input, Letters::Chars)
+                .unwrap()
+                .into_iter()
+                .map(|pair| pair.common)
+                .collect();
+            assert_eq!(all, expected, "{:?}", input);
+        }
+    }
 }
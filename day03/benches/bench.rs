@@ -1,15 +1,31 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use day03::{part1, part2};
+use day03::{part1, part1_squares, part2, part2_squares};
 
 fn part1_benchmark(c: &mut Criterion) {
     let input: Vec<&str> = include_str!("../../input/2018/day3.txt").lines().collect();
     c.bench_function("part1", move |b| b.iter(|| part1(&input)));
 }
 
+fn part1_squares_benchmark(c: &mut Criterion) {
+    let input: Vec<&str> = include_str!("../../input/2018/day3.txt").lines().collect();
+    c.bench_function("part1_squares", move |b| b.iter(|| part1_squares(&input)));
+}
+
 fn part2_benchmark(c: &mut Criterion) {
     let input: Vec<&str> = include_str!("../../input/2018/day3.txt").lines().collect();
     c.bench_function("part2", move |b| b.iter(|| part2(&input)));
 }
 
-criterion_group!(benches, part1_benchmark, part2_benchmark);
+fn part2_squares_benchmark(c: &mut Criterion) {
+    let input: Vec<&str> = include_str!("../../input/2018/day3.txt").lines().collect();
+    c.bench_function("part2_squares", move |b| b.iter(|| part2_squares(&input)));
+}
+
+criterion_group!(
+    benches,
+    part1_benchmark,
+    part1_squares_benchmark,
+    part2_benchmark,
+    part2_squares_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,51 @@
+use aoc_testgen::Lcg;
+use criterion::{criterion_group, criterion_main, Criterion, ParameterizedBenchmark};
+use day03::{part1_checked, part1_squares, Bounds};
+
+/// 1000 claims on a fabric `size` squares across, each up to a fifth of
+/// that in width and height.
+fn generate(size: u32) -> Vec<String> {
+    let mut rng = Lcg::new(12345);
+    let mut next = move |n: u32| rng.below(n);
+    (1..=1000)
+        .map(|id| {
+            let (w, h) = (1 + next(size / 5), 1 + next(size / 5));
+            let (x, y) = (next(size - w), next(size - h));
+            format!("#{} @ {},{}: {}x{}", id, x, y, w, h)
+        })
+        .collect()
+}
+
+fn part1_generated_benchmark(c: &mut Criterion) {
+    // The original implementation only up to 1000x1000, since it visits
+    // every claimed square.
+    c.bench(
+        "part1_generated",
+        ParameterizedBenchmark::new(
+            "sweep",
+            |b, &size| {
+                let claims = generate(size);
+                let input: Vec<&str> = claims.iter().map(String::as_str).collect();
+                b.iter(|| part1_checked(&input, Bounds::new(size, size)).unwrap())
+            },
+            vec![100, 1000, 1_000_000],
+        )
+        .sample_size(10),
+    );
+    c.bench(
+        "part1_generated",
+        ParameterizedBenchmark::new(
+            "squares",
+            |b, &size| {
+                let claims = generate(size);
+                let input: Vec<&str> = claims.iter().map(String::as_str).collect();
+                b.iter(|| part1_squares(&input))
+            },
+            vec![100, 1000],
+        )
+        .sample_size(10),
+    );
+}
+
+criterion_group!(benches, part1_generated_benchmark);
+criterion_main!(benches);
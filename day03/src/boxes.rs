@@ -0,0 +1,358 @@
+//! Claims on more than two axes, like `#1 @ 1,3,2: 4x4x6` for a claim
+//! which also lasts from time 2 to 8.
+//!
+//! The 2D functions are faster for flat claims; these slice the boxes
+//! along every axis but the last two and hand each slice to them.
+use std::str::FromStr;
+
+use crate::sweep::{compress, overlapping_spans};
+use crate::{area_at_least, ClaimId, Error, OverlapGraph, Rect};
+
+/// A box with sides parallel to the axes, covering the cells with
+/// `origin[axis] <= coordinate < origin[axis] + size[axis]` on every axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub origin: [u32; N],
+    pub size: [u32; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(origin: [u32; N], size: [u32; N]) -> Self {
+        Cuboid { origin, size }
+    }
+
+    /// The near edge on `axis`.
+    pub fn low(&self, axis: usize) -> u64 {
+        u64::from(self.origin[axis])
+    }
+
+    /// The far edge on `axis`, which isn't covered.
+    pub fn high(&self, axis: usize) -> u64 {
+        u64::from(self.origin[axis]) + u64::from(self.size[axis])
+    }
+
+    pub fn volume(&self) -> u128 {
+        self.size.iter().map(|&size| u128::from(size)).product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size.contains(&0)
+    }
+
+    /// Whether the boxes share at least one cell.
+    pub fn intersects(&self, other: &Self) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && (0..N)
+                .all(|axis| self.low(axis) < other.high(axis) && other.low(axis) < self.high(axis))
+    }
+}
+
+impl From<Rect> for Cuboid<2> {
+    fn from(rect: Rect) -> Self {
+        Cuboid::new([rect.x, rect.y], [rect.width, rect.height])
+    }
+}
+
+/// A claim in the `#id @ x,y,z: wxhxd` format, with `N` coordinates and
+/// sizes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoxClaim<const N: usize> {
+    pub id: ClaimId,
+    pub cuboid: Cuboid<N>,
+}
+
+/// Parses exactly `N` numbers separated by `separator`.
+fn numbers<const N: usize>(s: &str, separator: char) -> Result<[u32; N], ()> {
+    let mut numbers = [0; N];
+    let mut parts = s.split(separator);
+    for number in &mut numbers {
+        let part = parts.next().ok_or(())?;
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(());
+        }
+        *number = part.parse().map_err(|_| ())?;
+    }
+    match parts.next() {
+        Some(_) => Err(()),
+        None => Ok(numbers),
+    }
+}
+
+impl<const N: usize> FromStr for BoxClaim<N> {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let s = s.trim().strip_prefix('#').ok_or(())?;
+        let (id, s) = s.split_once(" @ ").ok_or(())?;
+        let (origin, size) = s.split_once(": ").ok_or(())?;
+        Ok(BoxClaim {
+            id: ClaimId(numbers::<1>(id, ' ')?[0]),
+            cuboid: Cuboid::new(numbers(origin, ',')?, numbers(size, 'x')?),
+        })
+    }
+}
+
+/// The total length of `intervals` covered at least `depth` times.
+fn length_at_least(intervals: impl Iterator<Item = (u64, u64)>, depth: usize) -> u64 {
+    let mut events: Vec<(u64, bool)> = intervals
+        .flat_map(|(low, high)| [(low, true), (high, false)])
+        .collect();
+    events.sort_unstable();
+    let (mut length, mut count, mut last) = (0, 0, 0);
+    for (x, adding) in events {
+        if count >= depth {
+            length += x - last;
+        }
+        if adding {
+            count += 1;
+        } else {
+            count -= 1;
+        }
+        last = x;
+    }
+    length
+}
+
+/// The volume covered `depth` or more times by `boxes`, on `axis` and the
+/// axes after it.
+fn measure<const N: usize>(boxes: &[&Cuboid<N>], axis: usize, depth: usize) -> u128 {
+    if boxes.len() < depth {
+        return 0;
+    }
+    match N - axis {
+        1 => u128::from(length_at_least(
+            boxes.iter().map(|b| (b.low(axis), b.high(axis))),
+            depth,
+        )),
+        2 => {
+            let rects: Vec<Rect> = boxes
+                .iter()
+                .map(|b| {
+                    Rect::new(
+                        b.origin[axis],
+                        b.origin[axis + 1],
+                        b.size[axis],
+                        b.size[axis + 1],
+                    )
+                })
+                .collect();
+            u128::from(area_at_least(&rects, depth))
+        }
+        _ => {
+            // Nothing starts or stops inside a slab between consecutive
+            // edges, so each is the same all the way through.
+            let edges = compress(boxes.iter().flat_map(|b| [b.low(axis), b.high(axis)]));
+            edges
+                .windows(2)
+                .map(|slab| {
+                    let inside: Vec<&Cuboid<N>> = boxes
+                        .iter()
+                        .copied()
+                        .filter(|b| b.low(axis) <= slab[0] && slab[1] <= b.high(axis))
+                        .collect();
+                    u128::from(slab[1] - slab[0]) * measure(&inside, axis + 1, depth)
+                })
+                .sum()
+        }
+    }
+}
+
+/// The number of cells claimed by `depth` or more of `boxes`.
+///
+/// Each axis but the last two is cut into slabs between the boxes' edges,
+/// so this is `O(n^(N - 1) log n)` for `n` boxes.
+///
+/// # Panics
+///
+/// If `depth` is zero, since that would include every unclaimed cell.
+pub fn volume_at_least<const N: usize>(boxes: &[Cuboid<N>], depth: usize) -> u128 {
+    assert!(depth > 0, "depth must be at least 1");
+    let boxes: Vec<&Cuboid<N>> = boxes.iter().filter(|b| !b.is_empty()).collect();
+    match N {
+        0 => 0,
+        _ => measure(&boxes, 0, depth),
+    }
+}
+
+/// The number of cells claimed by two or more of `boxes`.
+pub fn overlap_volume<const N: usize>(boxes: &[Cuboid<N>]) -> u128 {
+    volume_at_least(boxes, 2)
+}
+
+/// Every pair of indices into `boxes` whose boxes share a cell, in order.
+///
+/// Finds the pairs overlapping on the first two axes like
+/// [`overlapping_pairs`](crate::overlapping_pairs), then checks the rest
+/// of the axes for each of those, so it's `O(n log n + k * N)` for `n`
+/// boxes and `k` pairs overlapping on the first two axes.
+pub fn overlapping_box_pairs<const N: usize>(boxes: &[Cuboid<N>]) -> Vec<(usize, usize)> {
+    if N == 0 {
+        return Vec::new();
+    }
+    // With only one axis, sweeping across it is enough.
+    let second = if N > 1 { 1 } else { 0 };
+    let spans: Vec<Option<[(u64, u64); 2]>> = boxes
+        .iter()
+        .map(|b| (!b.is_empty()).then(|| [(b.low(0), b.high(0)), (b.low(second), b.high(second))]))
+        .collect();
+    let mut pairs = overlapping_spans(&spans);
+    if N > 2 {
+        pairs.retain(|&(i, j)| boxes[i].intersects(&boxes[j]));
+    }
+    pairs
+}
+
+/// Which of `boxes` overlap which.
+pub fn box_overlap_graph<const N: usize>(boxes: &[Cuboid<N>]) -> OverlapGraph {
+    OverlapGraph::from_pairs(
+        boxes.iter().map(Cuboid::is_empty),
+        overlapping_box_pairs(boxes),
+    )
+}
+
+/// The only claim which overlaps no other, like
+/// [`isolated_claim`](crate::isolated_claim).
+pub fn isolated_box_claim<const N: usize>(claims: &[BoxClaim<N>]) -> Result<&BoxClaim<N>, Error> {
+    let boxes: Vec<Cuboid<N>> = claims.iter().map(|claim| claim.cuboid).collect();
+    match box_overlap_graph(&boxes).isolated().as_slice() {
+        [] => Err(Error::NoIsolatedClaim),
+        &[only] => Ok(&claims[only]),
+        many => Err(Error::MultipleIsolatedClaims {
+            ids: many.iter().map(|&i| claims[i].id).collect(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use aoc_testgen::Lcg;
+
+    use super::{
+        isolated_box_claim, overlap_volume, overlapping_box_pairs, volume_at_least, BoxClaim,
+        Cuboid,
+    };
+    use crate::{area_at_least, overlapping_pairs, parse_claims, ClaimId, Error, Rect};
+
+    fn parse<const N: usize>(input: &[&str]) -> Vec<BoxClaim<N>> {
+        input.iter().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_box_claim() {
+        assert_eq!(
+            "#12 @ 1,2,3: 4x5x6".parse(),
+            Ok(BoxClaim {
+                id: ClaimId(12),
+                cuboid: Cuboid::new([1, 2, 3], [4, 5, 6]),
+            })
+        );
+        assert_eq!(
+            "#1 @ 1,3: 4x4".parse::<BoxClaim<2>>().map(|c| c.cuboid),
+            Ok(Rect::new(1, 3, 4, 4).into())
+        );
+        assert_eq!("#1 @ 1,3: 4x4".parse::<BoxClaim<3>>(), Err(()));
+        assert_eq!("#1 @ 1,3,5: 4x4".parse::<BoxClaim<3>>(), Err(()));
+        assert_eq!("#1 @ 1,3,5: 4x4x4x4".parse::<BoxClaim<3>>(), Err(()));
+        assert_eq!("#1 @ 1,-3,5: 4x4x4".parse::<BoxClaim<3>>(), Err(()));
+        assert_eq!("1 @ 1,3,5: 4x4x4".parse::<BoxClaim<3>>(), Err(()));
+    }
+
+    #[test]
+    fn test_boxes() {
+        let claims: Vec<BoxClaim<3>> = parse(&[
+            "#1 @ 1,3,0: 4x4x2",
+            "#2 @ 3,1,1: 4x4x2",
+            "#3 @ 5,5,0: 2x2x9",
+            // Flat on the third axis, so it covers nothing.
+            "#4 @ 0,0,0: 9x9x0",
+        ]);
+        let boxes: Vec<Cuboid<3>> = claims.iter().map(|claim| claim.cuboid).collect();
+        assert_eq!(overlap_volume(&boxes), 4);
+        assert_eq!(volume_at_least(&boxes, 1), 32 + 32 + 36 - 4);
+        assert_eq!(overlapping_box_pairs(&boxes), vec![(0, 1)]);
+        assert_eq!(
+            isolated_box_claim(&claims[..3]).map(|c| c.id),
+            Ok(ClaimId(3))
+        );
+        assert_eq!(isolated_box_claim(&claims).map(|c| c.id), Ok(ClaimId(3)));
+        assert_eq!(
+            isolated_box_claim(&claims[1..]),
+            Err(Error::MultipleIsolatedClaims {
+                ids: vec![ClaimId(2), ClaimId(3)]
+            })
+        );
+
+        // Apart in time, so no overlap.
+        let claims: Vec<BoxClaim<3>> = parse(&["#1 @ 1,3,0: 4x4x2", "#2 @ 3,1,2: 4x4x2"]);
+        let boxes: Vec<Cuboid<3>> = claims.iter().map(|claim| claim.cuboid).collect();
+        assert_eq!(overlap_volume(&boxes), 0);
+        assert!(overlapping_box_pairs(&boxes).is_empty());
+
+        // Intervals, with touching ends not overlapping.
+        let boxes = [
+            Cuboid::new([0], [5]),
+            Cuboid::new([5], [2]),
+            Cuboid::new([1], [5]),
+        ];
+        assert_eq!(overlapping_box_pairs(&boxes), vec![(0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn test_matches_2d() {
+        let claims = parse_claims(&["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"]);
+        let rects: Vec<Rect> = claims.iter().map(|claim| claim.rect).collect();
+        let boxes: Vec<Cuboid<2>> = rects.iter().map(|&rect| rect.into()).collect();
+        assert_eq!(overlap_volume(&boxes), u128::from(area_at_least(&rects, 2)));
+        assert_eq!(overlapping_box_pairs(&boxes), overlapping_pairs(&rects));
+    }
+
+    #[test]
+    fn test_matches_cells() {
+        let mut rng = Lcg::new(2018);
+        let mut next = move |n: u32| rng.below(n);
+        for _ in 0..30 {
+            let boxes: Vec<Cuboid<4>> = (0..8)
+                .map(|_| {
+                    let origin = [next(6), next(6), next(6), next(6)];
+                    let size = [next(4), next(4), next(4), next(4)];
+                    Cuboid::new(origin, size)
+                })
+                .collect();
+            let mut cells = [[[[0; 10]; 10]; 10]; 10];
+            for b in &boxes {
+                for w in b.low(0)..b.high(0) {
+                    for x in b.low(1)..b.high(1) {
+                        for y in b.low(2)..b.high(2) {
+                            for z in b.low(3)..b.high(3) {
+                                cells[w as usize][x as usize][y as usize][z as usize] += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            for depth in 1..4 {
+                let expected = cells
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .flatten()
+                    .filter(|&&n| n >= depth)
+                    .count() as u128;
+                assert_eq!(volume_at_least(&boxes, depth), expected, "{:?}", boxes);
+            }
+
+            let mut pairs = Vec::new();
+            for j in 0..boxes.len() {
+                for i in 0..j {
+                    if boxes[i].intersects(&boxes[j]) {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+            pairs.sort_unstable();
+            assert_eq!(overlapping_box_pairs(&boxes), pairs, "{:?}", boxes);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Claims which come and go, with the overlapping area and the isolated
+//! claims kept up to date as they do.
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+
+use hashbrown::HashMap;
+
+use crate::{Claim, ClaimId};
+
+struct Entry {
+    claim: Claim,
+    /// How many of the claim's squares other claims cover too.
+    shared: u64,
+}
+
+/// A set of claims, keyed by ID, which can be added to and withdrawn from
+/// one at a time.
+///
+/// Each change touches every square of the claim being added or removed,
+/// and nothing else, so it takes time proportional to the claim's area
+/// (times how deeply its squares are claimed).
+#[derive(Default)]
+pub struct ClaimSet {
+    /// Slots for the claims, reused once they're removed.
+    entries: Vec<Option<Entry>>,
+    free: Vec<usize>,
+    slots: HashMap<ClaimId, usize>,
+    /// The slots of the claims covering each claimed square.
+    squares: HashMap<(u64, u64), Vec<usize>>,
+    overlap_area: u64,
+    isolated: BTreeSet<ClaimId>,
+}
+
+impl ClaimSet {
+    pub fn new() -> Self {
+        ClaimSet::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn contains(&self, id: ClaimId) -> bool {
+        self.slots.contains_key(&id)
+    }
+
+    pub fn get(&self, id: ClaimId) -> Option<&Claim> {
+        self.slots.get(&id).map(|&slot| &self.entry(slot).claim)
+    }
+
+    fn entry(&self, slot: usize) -> &Entry {
+        self.entries[slot].as_ref().expect("slot must be in use")
+    }
+
+    fn entry_mut(&mut self, slot: usize) -> &mut Entry {
+        self.entries[slot].as_mut().expect("slot must be in use")
+    }
+
+    /// Adds `claim`, unless there's already one with its ID, in which case
+    /// nothing changes and this returns `false`.
+    pub fn insert(&mut self, claim: Claim) -> bool {
+        if self.slots.contains_key(&claim.id) {
+            return false;
+        }
+        let slot = self.free.pop().unwrap_or_else(|| {
+            self.entries.push(None);
+            self.entries.len() - 1
+        });
+        let rect = claim.rect;
+        self.slots.insert(claim.id, slot);
+        self.entries[slot] = Some(Entry { claim, shared: 0 });
+
+        let mut shared = 0;
+        for x in rect.left()..rect.right() {
+            for y in rect.top()..rect.bottom() {
+                let claims = self.squares.entry((x, y)).or_insert_with(Vec::new);
+                claims.push(slot);
+                let (depth, first) = (claims.len(), claims[0]);
+                if depth > 1 {
+                    shared += 1;
+                }
+                // The claim already here wasn't sharing this square before.
+                if depth == 2 {
+                    self.overlap_area += 1;
+                    self.entry_mut(first).shared += 1;
+                    self.update_isolated(first);
+                }
+            }
+        }
+        self.entry_mut(slot).shared = shared;
+        self.update_isolated(slot);
+        true
+    }
+
+    /// Withdraws the claim with ID `id`, if there is one.
+    pub fn remove(&mut self, id: ClaimId) -> Option<Claim> {
+        let slot = self.slots.remove(&id)?;
+        let rect = self.entry(slot).claim.rect;
+        for x in rect.left()..rect.right() {
+            for y in rect.top()..rect.bottom() {
+                let claims = self
+                    .squares
+                    .get_mut(&(x, y))
+                    .expect("claimed square must be tracked");
+                let position = claims
+                    .iter()
+                    .position(|&s| s == slot)
+                    .expect("claim must cover its squares");
+                claims.swap_remove(position);
+                match claims.first() {
+                    None => {
+                        self.squares.remove(&(x, y));
+                    }
+                    // The claim left here isn't sharing this square any more.
+                    Some(&other) if claims.len() == 1 => {
+                        self.overlap_area -= 1;
+                        self.entry_mut(other).shared -= 1;
+                        self.update_isolated(other);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        let entry = self.entries[slot].take().expect("slot must be in use");
+        self.free.push(slot);
+        self.isolated.remove(&entry.claim.id);
+        Some(entry.claim)
+    }
+
+    fn update_isolated(&mut self, slot: usize) {
+        let entry = self.entries[slot].as_ref().expect("slot must be in use");
+        if entry.shared == 0 && !entry.claim.rect.is_empty() {
+            self.isolated.insert(entry.claim.id);
+        } else {
+            self.isolated.remove(&entry.claim.id);
+        }
+    }
+
+    /// The number of squares claimed by two or more claims.
+    pub fn overlap_area(&self) -> u64 {
+        self.overlap_area
+    }
+
+    /// The claims which overlap no other, ordered by ID. Claims covering no
+    /// squares are never isolated.
+    pub fn isolated(&self) -> impl Iterator<Item = &Claim> + '_ {
+        self.isolated
+            .iter()
+            .map(move |id| &self.entry(self.slots[id]).claim)
+    }
+
+    /// Every claim, in no particular order.
+    pub fn claims(&self) -> impl Iterator<Item = &Claim> + '_ {
+        self.entries.iter().flatten().map(|entry| &entry.claim)
+    }
+}
+
+impl FromIterator<Claim> for ClaimSet {
+    fn from_iter<I: IntoIterator<Item = Claim>>(iter: I) -> Self {
+        let mut set = ClaimSet::new();
+        for claim in iter {
+            set.insert(claim);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use aoc_testgen::Lcg;
+
+    use super::ClaimSet;
+    use crate::{area_at_least, parse_claims, Claim, ClaimId, OverlapGraph, Rect};
+
+    fn isolated(set: &ClaimSet) -> Vec<u32> {
+        set.isolated().map(|claim| claim.id.0).collect()
+    }
+
+    #[test]
+    fn test_claim_set() {
+        let mut set: ClaimSet = parse_claims(&["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4"])
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.overlap_area(), 4);
+        assert_eq!(isolated(&set), Vec::<u32>::new());
+
+        assert!(set.insert("#3 @ 5,5: 2x2".parse().unwrap()));
+        assert!(!set.insert("#3 @ 0,0: 9x9".parse().unwrap()));
+        assert_eq!(set.get(ClaimId(3)).unwrap().rect, Rect::new(5, 5, 2, 2));
+        assert_eq!(set.overlap_area(), 4);
+        assert_eq!(isolated(&set), vec![3]);
+
+        // Three deep, touching all of them.
+        assert!(set.insert("#4 @ 4,4: 2x2".parse().unwrap()));
+        assert_eq!(set.overlap_area(), 7);
+        assert_eq!(isolated(&set), Vec::<u32>::new());
+
+        assert_eq!(set.remove(ClaimId(2)).unwrap().rect, Rect::new(3, 1, 4, 4));
+        assert_eq!(set.remove(ClaimId(2)), None);
+        assert!(!set.contains(ClaimId(2)));
+        assert_eq!(set.overlap_area(), 3);
+        assert_eq!(isolated(&set), Vec::<u32>::new());
+
+        set.remove(ClaimId(4));
+        assert_eq!(set.overlap_area(), 0);
+        assert_eq!(isolated(&set), vec![1, 3]);
+        set.remove(ClaimId(1));
+        set.remove(ClaimId(3));
+        assert!(set.is_empty());
+        assert_eq!(set.claims().count(), 0);
+    }
+
+    #[test]
+    fn test_claim_set_matches_batch() {
+        let mut rng = Lcg::new(777);
+        let mut next = move |n: u32| rng.below(n);
+        let mut set = ClaimSet::new();
+        for step in 0..300 {
+            let id = ClaimId(next(15));
+            if set.contains(id) && next(2) == 0 {
+                set.remove(id);
+            } else {
+                set.remove(id);
+                set.insert(Claim {
+                    id,
+                    rect: Rect::new(next(20), next(20), next(6), next(6)),
+                });
+            }
+
+            let mut claims: Vec<&Claim> = set.claims().collect();
+            claims.sort_by_key(|claim| claim.id);
+            let rects: Vec<Rect> = claims.iter().map(|claim| claim.rect).collect();
+            assert_eq!(set.overlap_area(), area_at_least(&rects, 2), "{}", step);
+            let expected: Vec<u32> = OverlapGraph::new(&rects)
+                .isolated()
+                .into_iter()
+                .map(|i| claims[i].id.0)
+                .collect();
+            assert_eq!(isolated(&set), expected, "{}", step);
+        }
+    }
+}
@@ -0,0 +1,456 @@
+//! How deeply the fabric is claimed, square by square.
+use std::mem;
+
+use crate::sweep::{compress, ActiveSpans, Coverage};
+use crate::Rect;
+
+/// An area claimed by the most claims anywhere on the fabric.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hotspot {
+    pub left: u64,
+    pub top: u64,
+    /// Exclusive, like [`Rect::right`].
+    pub right: u64,
+    /// Exclusive, like [`Rect::bottom`].
+    pub bottom: u64,
+    /// The indices of the claims covering it, in order.
+    pub claims: Vec<usize>,
+}
+
+impl Hotspot {
+    /// # Panics
+    ///
+    /// If the area doesn't fit in a `u64`.
+    pub fn area(&self) -> u64 {
+        (self.right - self.left)
+            .checked_mul(self.bottom - self.top)
+            .expect("area must fit in a u64")
+    }
+}
+
+/// How much of the fabric is claimed how many times.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The area claimed exactly `d` times, at `d - 1`.
+    areas: Vec<u64>,
+    hotspots: Vec<Hotspot>,
+}
+
+impl CoverageReport {
+    /// The most claims covering any one square, or 0 if nothing is
+    /// claimed.
+    pub fn max_depth(&self) -> usize {
+        self.areas.len()
+    }
+
+    /// The area claimed by exactly `depth` claims. Unclaimed area isn't
+    /// counted, so this is 0 for a `depth` of 0.
+    pub fn area(&self, depth: usize) -> u64 {
+        match depth {
+            0 => 0,
+            _ => self.areas.get(depth - 1).copied().unwrap_or(0),
+        }
+    }
+
+    /// The area claimed by `depth` or more claims.
+    ///
+    /// # Panics
+    ///
+    /// If the area doesn't fit in a `u64`.
+    pub fn area_at_least(&self, depth: usize) -> u64 {
+        self.areas
+            .iter()
+            .skip(depth.max(1) - 1)
+            .try_fold(0u64, |total, &area| total.checked_add(area))
+            .expect("area must fit in a u64")
+    }
+
+    /// Each depth from 1 to the maximum, with the area claimed exactly that
+    /// many times.
+    pub fn histogram(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.areas
+            .iter()
+            .enumerate()
+            .map(|(i, &area)| (i + 1, area))
+    }
+
+    /// The areas claimed [`max_depth`](Self::max_depth) times, ordered by
+    /// their left then top edges. Neighbouring squares covered by the same
+    /// claims are merged into one hotspot.
+    pub fn hotspots(&self) -> &[Hotspot] {
+        &self.hotspots
+    }
+}
+
+/// The left and right edge of every non-empty rectangle in `rects`, as
+/// `(x, adding, index)`, ordered by `x` with right edges first, so
+/// touching edges never count as overlapping.
+fn edges(rects: &[Rect]) -> Vec<(u64, bool, usize)> {
+    let mut events: Vec<(u64, bool, usize)> = rects
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| !r.is_empty())
+        .flat_map(|(i, r)| [(r.left(), true, i), (r.right(), false, i)])
+        .collect();
+    events.sort_unstable();
+    events
+}
+
+/// Calls `f` with each vertical strip between consecutive distinct edges,
+/// as `(left, right, counts)`, where `counts[i]` is how many of `rects`
+/// cover `ys[i]..ys[i + 1]` within the strip.
+pub(crate) fn for_each_strip(rects: &[Rect], ys: &[u64], mut f: impl FnMut(u64, u64, &[usize])) {
+    let mut counts = vec![0; ys.len().saturating_sub(1)];
+    let mut last_x = 0;
+    for (x, adding, i) in edges(rects) {
+        if x > last_x {
+            f(last_x, x, &counts);
+        }
+        let rect = &rects[i];
+        let from = ys
+            .binary_search(&rect.top())
+            .expect("top must be compressed");
+        let to = ys
+            .binary_search(&rect.bottom())
+            .expect("bottom must be compressed");
+        for count in &mut counts[from..to] {
+            if adding {
+                *count += 1;
+            } else {
+                *count -= 1;
+            }
+        }
+        last_x = x;
+    }
+}
+
+/// A segment tree over the gaps between consecutive compressed `ys`,
+/// tracking the most ranges covering any one gap as ranges are added and
+/// removed.
+struct Deepest {
+    ys: Vec<u64>,
+    /// How many ranges cover the whole of each node, without counting
+    /// ranges that only cover its ancestors or part of it.
+    count: Vec<usize>,
+    /// The most ranges covering any gap in each node, counting the ones
+    /// counted at it or its descendants.
+    max: Vec<usize>,
+}
+
+impl Deepest {
+    fn new(ys: Vec<u64>) -> Self {
+        let nodes = 4 * ys.len().max(2);
+        Deepest {
+            ys,
+            count: vec![0; nodes],
+            max: vec![0; nodes],
+        }
+    }
+
+    fn gaps(&self) -> usize {
+        self.ys.len().saturating_sub(1)
+    }
+
+    /// Add or remove a range covering `top..bottom`, which must both be
+    /// among the compressed `ys`.
+    fn add(&mut self, top: u64, bottom: u64, adding: bool) {
+        let from = self.ys.binary_search(&top).expect("top must be compressed");
+        let to = self
+            .ys
+            .binary_search(&bottom)
+            .expect("bottom must be compressed");
+        let gaps = self.gaps();
+        self.update(1, 0, gaps, from, to, adding);
+    }
+
+    /// Update `node`, which spans gaps `lo..hi`, for gaps `from..to`.
+    fn update(&mut self, node: usize, lo: usize, hi: usize, from: usize, to: usize, adding: bool) {
+        if to <= lo || hi <= from {
+            return;
+        }
+        if from <= lo && hi <= to {
+            if adding {
+                self.count[node] += 1;
+            } else {
+                self.count[node] -= 1;
+            }
+        } else {
+            let mid = (lo + hi) / 2;
+            self.update(2 * node, lo, mid, from, to, adding);
+            self.update(2 * node + 1, mid, hi, from, to, adding);
+        }
+        self.max[node] = self.count[node]
+            + if hi - lo == 1 {
+                0
+            } else {
+                self.max[2 * node].max(self.max[2 * node + 1])
+            };
+    }
+
+    /// The most ranges covering any one gap.
+    fn max(&self) -> usize {
+        self.max[1]
+    }
+
+    /// Calls `f` with each gap covered by [`max`](Self::max) ranges, in
+    /// order.
+    fn for_each_deepest(&self, mut f: impl FnMut(usize)) {
+        if self.max() > 0 {
+            self.visit(1, 0, self.gaps(), self.max(), &mut f);
+        }
+    }
+
+    /// Calls `f` with each gap in `node`, which spans gaps `lo..hi`, where
+    /// `node` and its descendants count `depth` ranges.
+    fn visit(&self, node: usize, lo: usize, hi: usize, depth: usize, f: &mut impl FnMut(usize)) {
+        if self.max[node] < depth {
+            return;
+        }
+        if hi - lo == 1 {
+            f(lo);
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let below = depth - self.count[node];
+        self.visit(2 * node, lo, mid, below, f);
+        self.visit(2 * node + 1, mid, hi, below, f);
+    }
+}
+
+/// How much of the fabric `rects` claim at each depth, and where they're
+/// deepest.
+///
+/// Sweeps across the rectangles' edges like
+/// [`area_at_least`](crate::area_at_least), once to find the maximum depth
+/// `d` and again to measure the area at each depth up to it, so it's
+/// `O(d * n log n)` for `n` rectangles. Finding the claims in each strip
+/// of a hotspot then takes `O(log n + d log d)`.
+///
+/// # Panics
+///
+/// If the area at any one depth doesn't fit in a `u64`, which claims with
+/// far edges past 2^32 can manage.
+pub fn coverage_report(rects: &[Rect]) -> CoverageReport {
+    let ys = compress(
+        rects
+            .iter()
+            .filter(|r| !r.is_empty())
+            .flat_map(|r| [r.top(), r.bottom()]),
+    );
+    let events = edges(rects);
+    let mut deepest = Deepest::new(ys.clone());
+    let mut max_depth = 0;
+    for &(_, adding, i) in &events {
+        deepest.add(rects[i].top(), rects[i].bottom(), adding);
+        max_depth = max_depth.max(deepest.max());
+    }
+    if max_depth == 0 {
+        return CoverageReport::default();
+    }
+
+    let mut coverage = Coverage::new(ys.clone(), max_depth);
+    let mut active = ActiveSpans::new(ys.clone(), rects.len());
+    let mut areas = vec![0u64; max_depth];
+    let mut hotspots: Vec<Hotspot> = Vec::new();
+    // The hotspots reaching the right edge of the last strip, which might
+    // carry on into this one, ordered by their top edges.
+    let mut open: Vec<usize> = Vec::new();
+    let mut last_x = 0;
+    for (x, adding, i) in events {
+        if x > last_x {
+            let (left, right) = (last_x, x);
+            for depth in 1..=max_depth {
+                let deeper = match depth {
+                    d if d == max_depth => 0,
+                    d => coverage.at_least(d + 1),
+                };
+                let area = (right - left)
+                    .checked_mul(coverage.at_least(depth) - deeper)
+                    .and_then(|area| areas[depth - 1].checked_add(area));
+                areas[depth - 1] = area.expect("area must fit in a u64");
+            }
+
+            let mut strip: Vec<Hotspot> = Vec::new();
+            if deepest.max() == max_depth {
+                deepest.for_each_deepest(|gap| {
+                    let (top, bottom) = (ys[gap], ys[gap + 1]);
+                    let mut claims = Vec::with_capacity(max_depth);
+                    active.for_each_containing(top, |j| claims.push(j));
+                    claims.sort_unstable();
+                    match strip.last_mut() {
+                        Some(last) if last.bottom == top && last.claims == claims => {
+                            last.bottom = bottom
+                        }
+                        _ => strip.push(Hotspot {
+                            left,
+                            top,
+                            right,
+                            bottom,
+                            claims,
+                        }),
+                    }
+                });
+            }
+            let mut previous = mem::take(&mut open).into_iter().peekable();
+            for hotspot in strip {
+                while previous
+                    .next_if(|&j| hotspots[j].top < hotspot.top)
+                    .is_some()
+                {}
+                match previous.peek() {
+                    Some(&j)
+                        if hotspots[j].top == hotspot.top
+                            && hotspots[j].bottom == hotspot.bottom
+                            && hotspots[j].claims == hotspot.claims =>
+                    {
+                        hotspots[j].right = right;
+                        open.push(j);
+                    }
+                    _ => {
+                        open.push(hotspots.len());
+                        hotspots.push(hotspot);
+                    }
+                }
+            }
+        }
+        let rect = &rects[i];
+        coverage.add(rect.top(), rect.bottom(), adding);
+        deepest.add(rect.top(), rect.bottom(), adding);
+        if adding {
+            active.insert(i, rect.top(), rect.bottom());
+        } else {
+            active.remove(i, rect.top());
+        }
+        last_x = x;
+    }
+    hotspots.sort_by_key(|h| (h.left, h.top));
+    CoverageReport { areas, hotspots }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use aoc_testgen::Lcg;
+
+    use super::{coverage_report, Hotspot};
+    use crate::Rect;
+
+    #[test]
+    fn test_coverage_report() {
+        let report = coverage_report(&[
+            Rect::new(1, 3, 4, 4),
+            Rect::new(3, 1, 4, 4),
+            Rect::new(5, 5, 2, 2),
+            Rect::new(4, 4, 2, 2),
+            Rect::new(0, 0, 0, 9),
+        ]);
+        assert_eq!(report.max_depth(), 3);
+        assert_eq!(
+            report.histogram().collect::<Vec<_>>(),
+            vec![(1, 25), (2, 6), (3, 1)]
+        );
+        assert_eq!(report.area(0), 0);
+        assert_eq!(report.area(2), 6);
+        assert_eq!(report.area(4), 0);
+        assert_eq!(report.area_at_least(2), 7);
+        assert_eq!(report.area_at_least(0), 32);
+        assert_eq!(
+            report.hotspots(),
+            &[Hotspot {
+                left: 4,
+                top: 4,
+                right: 5,
+                bottom: 5,
+                claims: vec![0, 1, 3],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_hotspots_merge() {
+        // Two claims stacked exactly, plus a third splitting the strips
+        // they're swept in without covering them.
+        let report = coverage_report(&[
+            Rect::new(0, 0, 4, 4),
+            Rect::new(0, 0, 4, 4),
+            Rect::new(2, 10, 1, 1),
+        ]);
+        assert_eq!(report.max_depth(), 2);
+        assert_eq!(
+            report.hotspots(),
+            &[Hotspot {
+                left: 0,
+                top: 0,
+                right: 4,
+                bottom: 4,
+                claims: vec![0, 1],
+            }]
+        );
+        assert_eq!(report.hotspots()[0].area(), 16);
+
+        // The same depth from different claims stays apart.
+        let report = coverage_report(&[
+            Rect::new(0, 0, 2, 2),
+            Rect::new(1, 0, 2, 2),
+            Rect::new(2, 0, 2, 2),
+        ]);
+        let spans: Vec<_> = report
+            .hotspots()
+            .iter()
+            .map(|h| (h.left, h.right, h.claims.clone()))
+            .collect();
+        assert_eq!(spans, vec![(1, 2, vec![0, 1]), (2, 3, vec![1, 2])]);
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = coverage_report(&[Rect::new(3, 3, 0, 0)]);
+        assert_eq!(report.max_depth(), 0);
+        assert_eq!(report.histogram().count(), 0);
+        assert!(report.hotspots().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "area must fit in a u64")]
+    fn test_coverage_report_overflow() {
+        // Each fits in a u64, but not both together.
+        let max = u32::MAX;
+        coverage_report(&[Rect::new(0, 0, max, max), Rect::new(max, 0, max, max)]);
+    }
+
+    #[test]
+    fn test_coverage_report_matches_squares() {
+        let mut rng = Lcg::new(54321);
+        let mut next = move |n: u32| rng.below(n);
+        for _ in 0..50 {
+            let rects: Vec<Rect> = (0..10)
+                .map(|_| Rect::new(next(20), next(20), next(8), next(8)))
+                .collect();
+            let mut grid = [[0; 28]; 28];
+            for rect in &rects {
+                for x in rect.x..rect.x + rect.width {
+                    for y in rect.y..rect.y + rect.height {
+                        grid[x as usize][y as usize] += 1;
+                    }
+                }
+            }
+            let report = coverage_report(&rects);
+            let max = *grid.iter().flatten().max().unwrap();
+            assert_eq!(report.max_depth(), max, "{:?}", rects);
+            for depth in 1..=max {
+                let expected = grid.iter().flatten().filter(|&&n| n == depth).count() as u64;
+                assert_eq!(report.area(depth), expected, "{} {:?}", depth, rects);
+            }
+            let hotspot_area: u64 = report.hotspots().iter().map(|h| h.area()).sum();
+            assert_eq!(hotspot_area, report.area(max), "{:?}", rects);
+            for hotspot in report.hotspots() {
+                assert_eq!(hotspot.claims.len(), max);
+                for x in hotspot.left..hotspot.right {
+                    for y in hotspot.top..hotspot.bottom {
+                        assert_eq!(grid[x as usize][y as usize], max);
+                    }
+                }
+            }
+        }
+    }
+}
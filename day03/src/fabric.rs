@@ -0,0 +1,165 @@
+use std::iter::FromIterator;
+
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+
+use crate::{Claim, Rect};
+
+/// A claim's squares as an R-tree envelope, with the claim's index.
+///
+/// R-tree rectangles include their far corner, so a claim covers
+/// `[x, y]` to `[right - 1, bottom - 1]`.
+type Entry = GeomWithData<Rectangle<[i64; 2]>, usize>;
+
+fn envelope(rect: &Rect) -> AABB<[i64; 2]> {
+    AABB::from_corners(
+        [rect.left() as i64, rect.top() as i64],
+        [rect.right() as i64 - 1, rect.bottom() as i64 - 1],
+    )
+}
+
+/// A set of claims indexed by where they are, for answering questions
+/// about particular squares or areas of the fabric.
+///
+/// Queries go through an R-tree, so they only look at claims near the
+/// area asked about.
+pub struct Fabric {
+    claims: Vec<Claim>,
+    tree: RTree<Entry>,
+}
+
+impl Fabric {
+    pub fn new(claims: Vec<Claim>) -> Self {
+        let entries = claims
+            .iter()
+            .enumerate()
+            .filter(|(_, claim)| !claim.rect.is_empty())
+            .map(|(i, claim)| {
+                let envelope = envelope(&claim.rect);
+                Entry::new(
+                    Rectangle::from_corners(envelope.lower(), envelope.upper()),
+                    i,
+                )
+            })
+            .collect();
+        Fabric {
+            claims,
+            tree: RTree::bulk_load(entries),
+        }
+    }
+
+    /// Every claim, in the order they were given.
+    pub fn claims(&self) -> &[Claim] {
+        &self.claims
+    }
+
+    fn in_order<'a>(&'a self, entries: impl Iterator<Item = &'a Entry>) -> Vec<&'a Claim> {
+        let mut indices: Vec<usize> = entries.map(|entry| entry.data).collect();
+        indices.sort_unstable();
+        indices.into_iter().map(|i| &self.claims[i]).collect()
+    }
+
+    /// The claims covering the square at `(x, y)`, in the order they were
+    /// given.
+    pub fn claims_at(&self, x: u32, y: u32) -> Vec<&Claim> {
+        self.in_order(self.tree.locate_all_at_point(&[i64::from(x), i64::from(y)]))
+    }
+
+    /// The claims sharing at least one square with `rect`, in the order
+    /// they were given.
+    pub fn claims_intersecting(&self, rect: &Rect) -> Vec<&Claim> {
+        if rect.is_empty() {
+            return Vec::new();
+        }
+        self.in_order(self.tree.locate_in_envelope_intersecting(&envelope(rect)))
+    }
+
+    /// How many claims cover the square at `(x, y)`.
+    pub fn coverage_at(&self, x: u32, y: u32) -> usize {
+        self.tree
+            .locate_all_at_point(&[i64::from(x), i64::from(y)])
+            .count()
+    }
+}
+
+impl FromIterator<Claim> for Fabric {
+    fn from_iter<I: IntoIterator<Item = Claim>>(iter: I) -> Self {
+        Fabric::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Fabric;
+    use crate::{Claim, ClaimId, Rect};
+
+    fn fabric() -> Fabric {
+        [
+            "#1 @ 1,3: 4x4",
+            "#2 @ 3,1: 4x4",
+            "#3 @ 5,5: 2x2",
+            "#4 @ 0,0: 0x9",
+        ]
+        .iter()
+        .map(|line| line.parse::<Claim>().unwrap())
+        .collect()
+    }
+
+    fn ids(claims: Vec<&Claim>) -> Vec<u32> {
+        claims.into_iter().map(|claim| claim.id.0).collect()
+    }
+
+    #[test]
+    fn test_claims_at() {
+        let fabric = fabric();
+        assert_eq!(ids(fabric.claims_at(3, 3)), vec![1, 2]);
+        assert_eq!(ids(fabric.claims_at(4, 4)), vec![1, 2]);
+        // The far edges aren't covered.
+        assert_eq!(ids(fabric.claims_at(5, 4)), vec![2]);
+        assert_eq!(ids(fabric.claims_at(7, 7)), Vec::<u32>::new());
+        assert_eq!(ids(fabric.claims_at(6, 6)), vec![3]);
+        // Empty claims cover nothing.
+        assert_eq!(ids(fabric.claims_at(0, 0)), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_claims_intersecting() {
+        let fabric = fabric();
+        assert_eq!(
+            ids(fabric.claims_intersecting(&Rect::new(4, 4, 2, 2))),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            ids(fabric.claims_intersecting(&Rect::new(0, 0, 3, 3))),
+            Vec::<u32>::new()
+        );
+        assert_eq!(
+            ids(fabric.claims_intersecting(&Rect::new(0, 0, 4, 4))),
+            vec![1, 2]
+        );
+        assert_eq!(
+            ids(fabric.claims_intersecting(&Rect::new(4, 4, 0, 2))),
+            Vec::<u32>::new()
+        );
+    }
+
+    #[test]
+    fn test_coverage_at() {
+        let fabric = fabric();
+        assert_eq!(fabric.coverage_at(3, 3), 2);
+        assert_eq!(fabric.coverage_at(1, 3), 1);
+        assert_eq!(fabric.coverage_at(0, 0), 0);
+        assert_eq!(fabric.claims().len(), 4);
+    }
+
+    #[test]
+    fn test_far_edges() {
+        let fabric = Fabric::new(vec![Claim {
+            id: ClaimId(1),
+            rect: Rect::new(u32::MAX - 1, u32::MAX, u32::MAX, 1),
+        }]);
+        assert_eq!(fabric.coverage_at(u32::MAX, u32::MAX), 1);
+        assert_eq!(fabric.coverage_at(u32::MAX, u32::MAX - 1), 0);
+    }
+}
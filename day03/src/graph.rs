@@ -0,0 +1,139 @@
+use crate::{overlapping_pairs, Rect};
+
+/// Which claims overlap which, by their index in the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OverlapGraph {
+    neighbours: Vec<Vec<usize>>,
+    /// Whether each claim covers no squares at all.
+    empty: Vec<bool>,
+}
+
+impl OverlapGraph {
+    pub fn new(rects: &[Rect]) -> Self {
+        OverlapGraph::from_pairs(rects.iter().map(Rect::is_empty), overlapping_pairs(rects))
+    }
+
+    /// The graph of claims with the given overlapping pairs, which must be
+    /// in order, like those from [`overlapping_pairs`]. `empty` says, for
+    /// each claim, whether it covers no squares.
+    pub fn from_pairs(
+        empty: impl IntoIterator<Item = bool>,
+        pairs: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Self {
+        let empty: Vec<bool> = empty.into_iter().collect();
+        let mut neighbours = vec![Vec::new(); empty.len()];
+        // The pairs come in order, so every list ends up sorted.
+        for (i, j) in pairs {
+            neighbours[i].push(j);
+            neighbours[j].push(i);
+        }
+        OverlapGraph { neighbours, empty }
+    }
+
+    /// The number of claims, overlapping or not.
+    pub fn len(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neighbours.is_empty()
+    }
+
+    /// The claims overlapping `claim`, in order.
+    pub fn neighbours(&self, claim: usize) -> &[usize] {
+        &self.neighbours[claim]
+    }
+
+    /// How many other claims overlap `claim`.
+    pub fn degree(&self, claim: usize) -> usize {
+        self.neighbours[claim].len()
+    }
+
+    /// Every overlapping pair, earlier claim first, in order.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbours
+            .iter()
+            .enumerate()
+            .flat_map(|(i, list)| list.iter().filter(move |&&j| j > i).map(move |&j| (i, j)))
+    }
+
+    /// Groups of claims linked by overlaps, each in order, and ordered by
+    /// their first claim. Isolated claims are groups of their own.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.len()];
+        let mut components = Vec::new();
+        for start in 0..self.len() {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut component = vec![start];
+            let mut stack = vec![start];
+            while let Some(claim) = stack.pop() {
+                for &next in &self.neighbours[claim] {
+                    if !seen[next] {
+                        seen[next] = true;
+                        component.push(next);
+                        stack.push(next);
+                    }
+                }
+            }
+            component.sort_unstable();
+            components.push(component);
+        }
+        components
+    }
+
+    /// The claims which overlap no other, in order. Claims covering no
+    /// squares don't count, since there's nothing of them to be isolated.
+    pub fn isolated(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&i| self.degree(i) == 0 && !self.empty[i])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::OverlapGraph;
+    use crate::Rect;
+
+    #[test]
+    fn test_overlap_graph() {
+        let graph = OverlapGraph::new(&[
+            Rect::new(0, 0, 3, 3),
+            Rect::new(10, 10, 2, 2),
+            Rect::new(2, 2, 3, 3),
+            Rect::new(4, 4, 2, 2),
+            Rect::new(11, 11, 5, 5),
+            Rect::new(20, 20, 1, 1),
+            Rect::new(1, 1, 0, 0),
+        ]);
+        assert_eq!(graph.len(), 7);
+        assert_eq!(graph.neighbours(2), &[0, 3]);
+        assert_eq!(
+            (0..graph.len())
+                .map(|i| graph.degree(i))
+                .collect::<Vec<_>>(),
+            vec![1, 1, 2, 1, 1, 0, 0]
+        );
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            vec![(0, 2), (1, 4), (2, 3)]
+        );
+        assert_eq!(
+            graph.components(),
+            vec![vec![0, 2, 3], vec![1, 4], vec![5], vec![6]]
+        );
+        assert_eq!(graph.isolated(), vec![5]);
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let graph = OverlapGraph::new(&[]);
+        assert!(graph.is_empty());
+        assert!(graph.components().is_empty());
+        assert!(graph.isolated().is_empty());
+    }
+}
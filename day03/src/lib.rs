@@ -1,18 +1,49 @@
+use std::fmt;
 use std::str::FromStr;
 
-use hashbrown::HashMap;
 use lazy_static::lazy_static;
 use regex::{self, Regex};
 
+mod boxes;
+mod claim_set;
+mod depth;
+mod fabric;
+mod graph;
+mod rect;
+pub mod render;
+mod squares;
+mod sweep;
+mod validate;
+
+pub use boxes::{
+    box_overlap_graph, isolated_box_claim, overlap_volume, overlapping_box_pairs, volume_at_least,
+    BoxClaim, Cuboid,
+};
+pub use claim_set::ClaimSet;
+pub use depth::{coverage_report, CoverageReport, Hotspot};
+pub use fabric::Fabric;
+pub use graph::OverlapGraph;
+pub use rect::Rect;
+pub use squares::{part1_squares, part2_squares};
+pub use sweep::{area_at_least, non_overlapping, overlap_area, overlapping_pairs};
+pub use validate::{check_claims, parse_claims_checked, Bounds, Diagnostic};
+
 const CLAIM_REGEX: &str = r"#(?P<id>\d+) @ (?P<x>\d+),(?P<y>\d+): (?P<w>\d+)x(?P<h>\d+)";
 
-#[derive(PartialEq, Eq, Hash, Debug, Clone)]
-struct Square(u32, u32);
+/// A claim's number, from the `#123` it starts with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClaimId(pub u32);
+
+impl fmt::Display for ClaimId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
 
-#[derive(Debug)]
-struct Claim {
-    id: String,
-    squares: Vec<Square>,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Claim {
+    pub id: ClaimId,
+    pub rect: Rect,
 }
 
 impl FromStr for Claim {
@@ -22,88 +53,242 @@ impl FromStr for Claim {
         lazy_static! {
             static ref RE: Regex = Regex::new(CLAIM_REGEX).unwrap();
         }
-        let caps = RE.captures(s).expect("claim must be in expected format");
-        let left = caps["x"].parse::<u32>().expect("x coord must be a number");
-        let right = left + caps["w"].parse::<u32>().expect("width must be a number");
-        let top = caps["y"].parse::<u32>().expect("y coord must be a number");
-        let bottom = top + caps["h"].parse::<u32>().expect("height must be a number");
+        let caps = RE.captures(s).ok_or(())?;
+        // The regex only matches digits, so these only fail if they're too
+        // big for a `u32`.
+        let number = |name: &str| caps[name].parse().map_err(|_| ());
         Ok(Claim {
-            id: caps["id"].to_string(),
-            squares: (left..right)
-                .flat_map(move |x| (top..bottom).map(move |y| Square(x, y)))
-                .collect(),
+            id: ClaimId(number("id")?),
+            rect: Rect {
+                x: number("x")?,
+                y: number("y")?,
+                width: number("w")?,
+                height: number("h")?,
+            },
         })
     }
 }
 
-// Convert each claim to a list of occupied squares, then create
-// a HashMap from Square to count, and find any with a count > 1.
-pub fn part1(input: &[&str]) -> usize {
-    input
-        .iter()
-        .map(|x| Claim::from_str(x).expect("could not parse claim"))
-        .fold(HashMap::new(), |mut acc, claim| {
-            for rect in claim.squares {
-                acc.entry(rect).and_modify(|count| *count += 1).or_insert(1);
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Some claims are wrong, as found by [`check_claims`] or
+    /// [`parse_claims_checked`].
+    InvalidClaims { diagnostics: Vec<Diagnostic> },
+    /// Every claim overlaps another.
+    NoIsolatedClaim,
+    /// More than one claim overlaps nothing, so there's no single answer.
+    MultipleIsolatedClaims { ids: Vec<ClaimId> },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidClaims { diagnostics } => {
+                write!(f, "{} problems with the claims:", diagnostics.len())?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n  {}", diagnostic)?;
+                }
+                Ok(())
             }
-            acc
-        })
+            Error::NoIsolatedClaim => write!(f, "every claim overlaps another"),
+            Error::MultipleIsolatedClaims { ids } => {
+                write!(f, "{} claims overlap nothing:", ids.len())?;
+                for (i, id) in ids.iter().enumerate() {
+                    write!(f, "{} {}", if i == 0 { "" } else { "," }, id)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub fn parse_claims(input: &[&str]) -> Vec<Claim> {
+    input
         .iter()
-        .filter(|(_, v)| **v > 1)
-        .count()
+        .map(|line| line.parse().expect("could not parse claim"))
+        .collect()
+}
+
+fn rects(claims: &[Claim]) -> Vec<Rect> {
+    claims.iter().map(|claim| claim.rect).collect()
+}
+
+fn parse_claims_within(input: &[&str], bounds: Bounds) -> Result<Vec<Claim>, Error> {
+    parse_claims_checked(input, bounds).map_err(|diagnostics| Error::InvalidClaims { diagnostics })
+}
+
+/// # Panics
+///
+/// If a claim is malformed or doesn't fit on the puzzle's 1000x1000
+/// fabric; use `part1_checked` to handle those.
+pub fn part1(input: &[&str]) -> u64 {
+    part1_checked(input, Bounds::default()).unwrap_or_else(|e| panic!("{}", e))
 }
 
-// Determine the number of claims per rectangle, then look for
-// claims whose rectangles only have a single claim.
+/// The number of squares claimed more than once, once every claim has been
+/// checked against `bounds`.
+pub fn part1_checked(input: &[&str], bounds: Bounds) -> Result<u64, Error> {
+    let claims = parse_claims_within(input, bounds)?;
+    Ok(area_at_least(&rects(&claims), 2))
+}
+
+fn find_isolated(claims: &[Claim]) -> Result<&Claim, Error> {
+    match OverlapGraph::new(&rects(claims)).isolated().as_slice() {
+        [] => Err(Error::NoIsolatedClaim),
+        &[only] => Ok(&claims[only]),
+        many => Err(Error::MultipleIsolatedClaims {
+            ids: many.iter().map(|&i| claims[i].id).collect(),
+        }),
+    }
+}
+
+/// The only claim which overlaps no other, once the claims have been checked
+/// against `bounds`.
+pub fn isolated_claim(claims: &[Claim], bounds: Bounds) -> Result<&Claim, Error> {
+    let diagnostics = check_claims(claims, bounds);
+    if !diagnostics.is_empty() {
+        return Err(Error::InvalidClaims { diagnostics });
+    }
+    find_isolated(claims)
+}
+
+/// # Panics
+///
+/// If a claim is malformed or doesn't fit on the puzzle's 1000x1000
+/// fabric, or there isn't exactly one claim which overlaps no other; use
+/// `part2_checked` to handle those.
 pub fn part2(input: &[&str]) -> String {
-    let claims: Vec<Claim> = input
-        .iter()
-        .map(|line| Claim::from_str(line).unwrap())
-        .collect();
-    let claims_per_rectangle =
-        claims
-            .iter()
-            .fold(HashMap::new(), |mut acc: HashMap<Square, usize>, claim| {
-                for rect in &claim.squares {
-                    acc.entry(rect.clone())
-                        .and_modify(|count| *count += 1)
-                        .or_insert(1);
-                }
-                acc
-            });
-    claims
-        .into_iter()
-        .filter_map(|claim| {
-            // Check if any squares have more than one claim.
-            // If not, return the ID.
-            if claim
-                .squares
-                .iter()
-                .any(|rect| claims_per_rectangle.get(rect) != Some(&1))
-            {
-                None
-            } else {
-                Some(claim.id)
-            }
-        })
-        .next()
-        .expect("a valid claim")
+    part2_checked(input, Bounds::default())
+        .map(|id| id.0.to_string())
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// The ID of the only claim which overlaps no other, once every claim has
+/// been checked against `bounds`.
+pub fn part2_checked(input: &[&str], bounds: Bounds) -> Result<ClaimId, Error> {
+    let claims = parse_claims_within(input, bounds)?;
+    find_isolated(&claims).map(|claim| claim.id)
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::{part1, part2};
+    use super::{
+        isolated_claim, parse_claims, part1, part1_checked, part1_squares, part2, part2_checked,
+        part2_squares, Bounds, Claim, ClaimId, Diagnostic, Error, Rect,
+    };
 
     #[test]
     fn test_part1() {
         let input = &["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"];
         assert_eq!(part1(input), 4);
+        assert_eq!(part1_squares(input), 4);
     }
 
     #[test]
     fn test_part2() {
         let input = &["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"];
         assert_eq!(part2(input), "3");
+        assert_eq!(part2_squares(input), "3");
+    }
+
+    #[test]
+    fn test_parse_claim() {
+        assert_eq!(
+            "#123 @ 3,2: 5x4".parse(),
+            Ok(Claim {
+                id: ClaimId(123),
+                rect: Rect::new(3, 2, 5, 4),
+            })
+        );
+        assert_eq!("#1 @ 3,2 5x4".parse::<Claim>(), Err(()));
+        assert_eq!("#1 @ 3,2: 5x4294967296".parse::<Claim>(), Err(()));
+    }
+
+    #[test]
+    fn test_isolated_claim() {
+        let claims = parse_claims(&["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"]);
+        assert_eq!(
+            isolated_claim(&claims, Bounds::default()).map(|c| c.id),
+            Ok(ClaimId(3))
+        );
+        let claims = parse_claims(&["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4"]);
+        assert_eq!(
+            isolated_claim(&claims, Bounds::default()),
+            Err(Error::NoIsolatedClaim)
+        );
+        let claims = parse_claims(&["#1 @ 1,3: 4x4", "#7 @ 9,9: 1x1", "#8 @ 0,0: 1x1"]);
+        let err = isolated_claim(&claims, Bounds::default()).unwrap_err();
+        assert_eq!(
+            err,
+            Error::MultipleIsolatedClaims {
+                ids: vec![ClaimId(1), ClaimId(7), ClaimId(8)]
+            }
+        );
+        assert_eq!(err.to_string(), "3 claims overlap nothing: #1, #7, #8");
+
+        let claims = parse_claims(&["#1 @ 1,3: 4x4", "#2 @ 5,5: 0x2", "#3 @ 999,0: 2x1"]);
+        let err = isolated_claim(&claims, Bounds::default()).unwrap_err();
+        assert_eq!(
+            err,
+            Error::InvalidClaims {
+                diagnostics: vec![
+                    Diagnostic::ZeroArea {
+                        line: 1,
+                        id: ClaimId(2)
+                    },
+                    Diagnostic::OutOfBounds {
+                        line: 2,
+                        id: ClaimId(3),
+                        rect: Rect::new(999, 0, 2, 1),
+                        bounds: Default::default(),
+                    },
+                ]
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "2 problems with the claims:\n  line 2: #2 covers no squares\n  \
+             line 3: #3 reaches (1001, 1), off the 1000x1000 fabric"
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let input = &["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 2000,1500: 2x2"];
+        let bounds = Bounds::new(3000, 2000);
+        assert_eq!(part1_checked(input, bounds), Ok(4));
+        assert_eq!(part2_checked(input, bounds), Ok(ClaimId(3)));
+        let claims = parse_claims(input);
+        assert_eq!(
+            isolated_claim(&claims, bounds).map(|c| c.id),
+            Ok(ClaimId(3))
+        );
+        let small = Bounds::new(2000, 2000);
+        let out_of_bounds = Error::InvalidClaims {
+            diagnostics: vec![Diagnostic::OutOfBounds {
+                line: 2,
+                id: ClaimId(3),
+                rect: Rect::new(2000, 1500, 2, 2),
+                bounds: small,
+            }],
+        };
+        assert_eq!(part1_checked(input, small), Err(out_of_bounds.clone()));
+        assert_eq!(part2_checked(input, small), Err(out_of_bounds.clone()));
+        assert_eq!(isolated_claim(&claims, small), Err(out_of_bounds));
+    }
+
+    #[test]
+    #[should_panic(expected = "line 2: not a claim")]
+    fn test_part1_malformed() {
+        part1(&["#1 @ 1,3: 4x4", "#2 @ 3,1 4x4"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "line 1: #1 covers no squares")]
+    fn test_part2_zero_area() {
+        part2(&["#1 @ 1,3: 0x4", "#2 @ 3,1: 4x4"]);
     }
 }
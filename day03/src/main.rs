@@ -1,7 +1,46 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::process;
+
 use day03;
+use day03::render::{self, Picture};
+use day03::Claim;
+
+/// Renders the claims to `path`, in the format its extension asks for.
+fn render(claims: &[Claim], path: &Path) -> io::Result<()> {
+    let create = || File::create(path).map(BufWriter::new);
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("svg") => render::write_svg(claims, create()?),
+        Some("ppm") => Picture::new(claims).write_ppm(create()?),
+        #[cfg(feature = "png")]
+        Some("png") => Picture::new(claims).write_png(create()?),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "can only render to .svg, .ppm or .png (with the png feature) files",
+        )),
+    }
+}
 
 fn main() {
     let input: Vec<&str> = include_str!("../../input/2018/day3.txt").lines().collect();
+    let args: Vec<String> = env::args().collect();
+    let output = match args.as_slice() {
+        [_] => None,
+        [_, flag, path] if flag == "--render" => Some(path),
+        _ => {
+            eprintln!("usage: {} [--render <fabric.svg|ppm|png>]", args[0]);
+            process::exit(2);
+        }
+    };
     println!("Part 1: {}", day03::part1(&input));
     println!("Part 2: {}", day03::part2(&input));
+    if let Some(path) = output {
+        let claims = day03::parse_claims(&input);
+        if let Err(e) = render(&claims, Path::new(path)) {
+            eprintln!("could not render to {}: {}", path, e);
+            process::exit(1);
+        }
+    }
 }
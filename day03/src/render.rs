@@ -0,0 +1,306 @@
+//! Pictures of the fabric, for checking claims land where they should.
+//!
+//! Each claim gets its own colour, squares claimed more than once are
+//! shaded red, darker the more claims overlap there, and claims which
+//! overlap nothing (the answer to part 2) are picked out in gold.
+use std::io::{self, Write};
+
+use crate::depth::for_each_strip;
+use crate::sweep::compress;
+use crate::{Claim, OverlapGraph, Rect};
+
+const BACKGROUND: [u8; 3] = [24, 24, 24];
+const HIGHLIGHT: [u8; 3] = [255, 215, 0];
+
+/// The most pixels [`Picture::new`] will allocate.
+const MAX_PIXELS: u64 = 1 << 28;
+
+/// A colour for claim `index`, spread around the greens, blues and
+/// purples so neighbouring claims look different and don't get mistaken
+/// for overlaps.
+fn claim_colour(index: usize) -> [u8; 3] {
+    let hue = 0.2 + 0.55 * (index as f64 * 0.618_033_988_749_895).fract();
+    let (s, v) = (0.5, 0.85);
+    let sector = hue * 6.0;
+    let f = sector.fract();
+    let (p, q, t) = (v * (1.0 - s), v * (1.0 - s * f), v * (1.0 - s * (1.0 - f)));
+    let (r, g, b) = match sector as u32 {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    [(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8]
+}
+
+/// Shades of red from light, where two claims overlap, to dark, where
+/// `max_depth` do.
+fn overlap_colour(depth: usize, max_depth: usize) -> [u8; 3] {
+    let t = (depth - 2) as f64 / (max_depth.max(3) - 2) as f64;
+    let mix = |from: f64, to: f64| (from + (to - from) * t) as u8;
+    [mix(255.0, 150.0), mix(140.0, 0.0), mix(140.0, 0.0)]
+}
+
+/// Whether each claim overlaps nothing, and so gets highlighted.
+fn isolated(claims: &[Claim]) -> Vec<bool> {
+    let rects: Vec<Rect> = claims.iter().map(|claim| claim.rect).collect();
+    let mut isolated = vec![false; claims.len()];
+    for i in OverlapGraph::new(&rects).isolated() {
+        isolated[i] = true;
+    }
+    isolated
+}
+
+/// The far corner of the claimed fabric, which is drawn from `(0, 0)`.
+fn bounds(claims: &[Claim]) -> (u64, u64) {
+    claims
+        .iter()
+        .filter(|claim| !claim.rect.is_empty())
+        .fold((0, 0), |(width, height), claim| {
+            (
+                width.max(claim.rect.right()),
+                height.max(claim.rect.bottom()),
+            )
+        })
+}
+
+/// The fabric with one pixel per square.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Picture {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Picture {
+    /// Paints every square of every claim, so this is only meant for
+    /// fabrics small enough to look at.
+    ///
+    /// # Panics
+    ///
+    /// If the claimed fabric is more than 2^28 squares.
+    pub fn new(claims: &[Claim]) -> Self {
+        let (width, height) = bounds(claims);
+        assert!(
+            width
+                .checked_mul(height)
+                .is_some_and(|pixels| pixels <= MAX_PIXELS),
+            "fabric is too large to render one pixel per square"
+        );
+        let (width, height) = (width as usize, height as usize);
+        let mut depths = vec![0; width * height];
+        let mut owners = vec![0; width * height];
+        for (i, claim) in claims.iter().enumerate() {
+            let rect = &claim.rect;
+            for y in rect.top() as usize..rect.bottom() as usize {
+                for x in rect.left() as usize..rect.right() as usize {
+                    depths[y * width + x] += 1;
+                    owners[y * width + x] = i;
+                }
+            }
+        }
+        let max_depth = depths.iter().copied().max().unwrap_or(0);
+        let isolated = isolated(claims);
+        let pixels = depths
+            .iter()
+            .zip(&owners)
+            .map(|(&depth, &owner)| match depth {
+                0 => BACKGROUND,
+                1 if isolated[owner] => HIGHLIGHT,
+                1 => claim_colour(owner),
+                _ => overlap_colour(depth, max_depth),
+            })
+            .collect();
+        Picture {
+            width: width as u32,
+            height: height as u32,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The colour of the square at `(x, y)`, as RGB.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        assert!(x < self.width && y < self.height, "pixel out of bounds");
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.pixels.iter().flatten().copied().collect()
+    }
+
+    /// Writes the picture as a binary PPM.
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.bytes())
+    }
+
+    /// Writes the picture as an 8-bit RGB PNG.
+    #[cfg(feature = "png")]
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.bytes()))
+            .map_err(io::Error::other)
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Writes the fabric as an SVG, one unit per square.
+///
+/// Unlike [`Picture`] it's drawn from the claims' edges, so it stays small
+/// however big the fabric is. Each claim has its ID as a tooltip.
+pub fn write_svg<W: Write>(claims: &[Claim], mut writer: W) -> io::Result<()> {
+    let (width, height) = bounds(claims);
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="{w}" height="{h}" shape-rendering="crispEdges">"#,
+        w = width,
+        h = height
+    )?;
+    writeln!(
+        writer,
+        r#"<rect width="{}" height="{}" fill="{}"/>"#,
+        width,
+        height,
+        hex(BACKGROUND)
+    )?;
+
+    let isolated = isolated(claims);
+    for (i, claim) in claims.iter().enumerate() {
+        let rect = &claim.rect;
+        if rect.is_empty() {
+            continue;
+        }
+        let colour = if isolated[i] {
+            HIGHLIGHT
+        } else {
+            claim_colour(i)
+        };
+        writeln!(
+            writer,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"><title>{}</title></rect>"#,
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            hex(colour),
+            claim.id
+        )?;
+    }
+
+    // Runs of squares at the same depth down each strip between edges.
+    let rects: Vec<Rect> = claims.iter().map(|claim| claim.rect).collect();
+    let ys = compress(
+        rects
+            .iter()
+            .filter(|r| !r.is_empty())
+            .flat_map(|r| [r.top(), r.bottom()]),
+    );
+    let mut overlaps: Vec<(u64, u64, u64, u64, usize)> = Vec::new();
+    for_each_strip(&rects, &ys, |left, right, counts| {
+        let mut i = 0;
+        while i < counts.len() {
+            let depth = counts[i];
+            let start = i;
+            while i < counts.len() && counts[i] == depth {
+                i += 1;
+            }
+            if depth > 1 {
+                overlaps.push((left, ys[start], right, ys[i], depth));
+            }
+        }
+    });
+    let max_depth = overlaps.iter().map(|o| o.4).max().unwrap_or(0);
+    for (left, top, right, bottom, depth) in overlaps {
+        writeln!(
+            writer,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"><title>{} claims</title></rect>"#,
+            left,
+            top,
+            right - left,
+            bottom - top,
+            hex(overlap_colour(depth, max_depth)),
+            depth
+        )?;
+    }
+    writeln!(writer, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{claim_colour, write_svg, Picture, BACKGROUND, HIGHLIGHT};
+    use crate::parse_claims;
+
+    #[test]
+    fn test_picture() {
+        let claims = parse_claims(&["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"]);
+        let picture = Picture::new(&claims);
+        assert_eq!((picture.width(), picture.height()), (7, 7));
+        assert_eq!(picture.pixel(0, 0), BACKGROUND);
+        // Claims cover `left..right`, so #1 stops just short of x = 5.
+        assert_eq!(picture.pixel(1, 3), claim_colour(0));
+        assert_eq!(picture.pixel(4, 6), claim_colour(0));
+        assert_eq!(picture.pixel(5, 6), HIGHLIGHT);
+        assert_eq!(picture.pixel(6, 1), claim_colour(1));
+        assert_eq!(picture.pixel(6, 6), HIGHLIGHT);
+        let overlap = picture.pixel(3, 3);
+        assert!(overlap != BACKGROUND && overlap != claim_colour(0) && overlap != claim_colour(1));
+        assert_eq!(picture.pixel(4, 4), overlap);
+        assert_eq!(picture.pixel(5, 5), HIGHLIGHT);
+
+        let mut ppm = Vec::new();
+        picture.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n7 7\n255\n"));
+        assert_eq!(ppm.len(), 11 + 7 * 7 * 3);
+        assert_eq!(&ppm[11..14], &BACKGROUND);
+    }
+
+    #[test]
+    #[should_panic(expected = "too large to render")]
+    fn test_picture_too_large() {
+        // Nearly 2^33 wide by 2^32 high, so the area overflows a u64.
+        Picture::new(&parse_claims(&["#1 @ 4294967295,0: 4294967295x4294967295"]));
+    }
+
+    #[test]
+    fn test_overlap_shading() {
+        let claims = parse_claims(&["#1 @ 0,0: 3x1", "#2 @ 1,0: 2x1", "#3 @ 2,0: 1x1"]);
+        let picture = Picture::new(&claims);
+        let brightness = |[r, g, b]: [u8; 3]| u32::from(r) + u32::from(g) + u32::from(b);
+        assert!(brightness(picture.pixel(1, 0)) > brightness(picture.pixel(2, 0)));
+    }
+
+    #[test]
+    fn test_svg() {
+        let claims = parse_claims(&["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"]);
+        let mut svg = Vec::new();
+        write_svg(&claims, &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"viewBox="0 0 7 7""#));
+        assert!(svg.contains(r#"<rect x="1" y="3" width="4" height="4""#));
+        assert!(svg.contains(
+            r##"<rect x="5" y="5" width="2" height="2" fill="#ffd700"><title>#3</title>"##
+        ));
+        assert!(svg.contains(r#"<rect x="3" y="3" width="2" height="2""#));
+        assert!(svg.contains("<title>2 claims</title>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
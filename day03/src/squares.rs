@@ -0,0 +1,72 @@
+//! The original implementation, which expands every claim into the
+//! squares it covers. Kept to benchmark against.
+use hashbrown::HashMap;
+
+use crate::{Claim, Rect};
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+struct Square(u64, u64);
+
+fn squares(rect: &Rect) -> Vec<Square> {
+    let (top, bottom) = (rect.top(), rect.bottom());
+    (rect.left()..rect.right())
+        .flat_map(move |x| (top..bottom).map(move |y| Square(x, y)))
+        .collect()
+}
+
+// Convert each claim to a list of occupied squares, then create
+// a HashMap from Square to count, and find any with a count > 1.
+pub fn part1_squares(input: &[&str]) -> usize {
+    input
+        .iter()
+        .map(|x| x.parse::<Claim>().expect("could not parse claim"))
+        .fold(HashMap::new(), |mut acc, claim| {
+            for rect in squares(&claim.rect) {
+                acc.entry(rect).and_modify(|count| *count += 1).or_insert(1);
+            }
+            acc
+        })
+        .iter()
+        .filter(|(_, v)| **v > 1)
+        .count()
+}
+
+// Determine the number of claims per rectangle, then look for
+// claims whose rectangles only have a single claim.
+pub fn part2_squares(input: &[&str]) -> String {
+    let claims: Vec<(Claim, Vec<Square>)> = input
+        .iter()
+        .map(|line| {
+            let claim: Claim = line.parse().unwrap();
+            let squares = squares(&claim.rect);
+            (claim, squares)
+        })
+        .collect();
+    let claims_per_rectangle = claims.iter().fold(
+        HashMap::new(),
+        |mut acc: HashMap<Square, usize>, (_, squares)| {
+            for rect in squares {
+                acc.entry(rect.clone())
+                    .and_modify(|count| *count += 1)
+                    .or_insert(1);
+            }
+            acc
+        },
+    );
+    claims
+        .into_iter()
+        .filter_map(|(claim, squares)| {
+            // Check if any squares have more than one claim.
+            // If not, return the ID.
+            if squares
+                .iter()
+                .any(|rect| claims_per_rectangle.get(rect) != Some(&1))
+            {
+                None
+            } else {
+                Some(claim.id.0.to_string())
+            }
+        })
+        .next()
+        .expect("a valid claim")
+}
@@ -0,0 +1,434 @@
+//! Overlaps worked out from the claimed rectangles themselves, so the
+//! cost depends on how many claims there are rather than how big they
+//! are.
+use std::collections::BTreeSet;
+
+use crate::Rect;
+
+/// The distinct values, sorted, so a coordinate can be replaced by its
+/// index.
+pub(crate) fn compress(values: impl Iterator<Item = u64>) -> Vec<u64> {
+    let mut values: Vec<u64> = values.collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+/// A segment tree over the gaps between consecutive compressed `ys`,
+/// tracking how much of the axis is covered at least once, twice, and so
+/// on up to `depth` times as ranges are added and removed.
+pub(crate) struct Coverage {
+    ys: Vec<u64>,
+    depth: usize,
+    /// How many ranges cover the whole of each node, without counting
+    /// ranges that only cover its ancestors or part of it.
+    count: Vec<usize>,
+    /// How much of each node is covered at least `d` times by the ranges
+    /// counted at it or its descendants, at `node * depth + d - 1`.
+    at_least: Vec<u64>,
+}
+
+impl Coverage {
+    pub(crate) fn new(ys: Vec<u64>, depth: usize) -> Self {
+        let nodes = 4 * ys.len().max(2);
+        Coverage {
+            ys,
+            depth,
+            count: vec![0; nodes],
+            at_least: vec![0; nodes * depth],
+        }
+    }
+
+    fn gaps(&self) -> usize {
+        self.ys.len().saturating_sub(1)
+    }
+
+    /// Add or remove a range covering `top..bottom`, which must both be
+    /// among the compressed `ys`.
+    pub(crate) fn add(&mut self, top: u64, bottom: u64, adding: bool) {
+        let from = self.ys.binary_search(&top).expect("top must be compressed");
+        let to = self
+            .ys
+            .binary_search(&bottom)
+            .expect("bottom must be compressed");
+        let gaps = self.gaps();
+        self.update(1, 0, gaps, from, to, adding);
+    }
+
+    /// Update `node`, which spans gaps `lo..hi`, for gaps `from..to`.
+    fn update(&mut self, node: usize, lo: usize, hi: usize, from: usize, to: usize, adding: bool) {
+        if to <= lo || hi <= from {
+            return;
+        }
+        if from <= lo && hi <= to {
+            if adding {
+                self.count[node] += 1;
+            } else {
+                self.count[node] -= 1;
+            }
+        } else {
+            let mid = (lo + hi) / 2;
+            self.update(2 * node, lo, mid, from, to, adding);
+            self.update(2 * node + 1, mid, hi, from, to, adding);
+        }
+        self.pull(node, lo, hi);
+    }
+
+    fn pull(&mut self, node: usize, lo: usize, hi: usize) {
+        let full = self.ys[hi] - self.ys[lo];
+        let count = self.count[node];
+        for d in 1..=self.depth {
+            // Anything covered `d - count` times below is covered `d`
+            // times with the ranges counted here.
+            self.at_least[node * self.depth + d - 1] = if d <= count {
+                full
+            } else if hi - lo == 1 {
+                0
+            } else {
+                let below = d - count - 1;
+                self.at_least[2 * node * self.depth + below]
+                    + self.at_least[(2 * node + 1) * self.depth + below]
+            };
+        }
+    }
+
+    /// How much of the whole axis is covered at least `d` times, for `d`
+    /// from 1 to `depth`.
+    pub(crate) fn at_least(&self, d: usize) -> u64 {
+        self.at_least[self.depth + d - 1]
+    }
+
+    /// How much of the whole axis is covered at least `depth` times.
+    fn covered(&self) -> u64 {
+        self.at_least(self.depth)
+    }
+}
+
+/// The number of squares claimed by `depth` or more of `rects`.
+///
+/// Sweeps a vertical line across the rectangles' left and right edges,
+/// keeping track of how much of the line is covered at least `depth`
+/// times, in `O(depth * n log n)` for `n` rectangles.
+///
+/// # Panics
+///
+/// If `depth` is zero, since that would include the whole unclaimed
+/// fabric, or if the area doesn't fit in a `u64`, which claims with far
+/// edges past 2^32 can manage.
+pub fn area_at_least(rects: &[Rect], depth: usize) -> u64 {
+    assert!(depth > 0, "depth must be at least 1");
+    let rects: Vec<&Rect> = rects.iter().filter(|r| !r.is_empty()).collect();
+    let mut events: Vec<(u64, bool, &Rect)> = rects
+        .iter()
+        .flat_map(|&r| [(r.left(), true, r), (r.right(), false, r)])
+        .collect();
+    // Removals first, so touching edges never count as overlapping.
+    events.sort_unstable_by_key(|&(x, adding, _)| (x, adding));
+    let ys = compress(rects.iter().flat_map(|r| [r.top(), r.bottom()]));
+    let mut coverage = Coverage::new(ys, depth);
+
+    let mut area: u64 = 0;
+    let mut last_x = 0;
+    for (x, adding, rect) in events {
+        area = coverage
+            .covered()
+            .checked_mul(x - last_x)
+            .and_then(|strip| area.checked_add(strip))
+            .expect("area must fit in a u64");
+        coverage.add(rect.top(), rect.bottom(), adding);
+        last_x = x;
+    }
+    area
+}
+
+/// The number of squares claimed by two or more of `rects`.
+///
+/// # Panics
+///
+/// If the area doesn't fit in a `u64`, as for [`area_at_least`].
+pub fn overlap_area(rects: &[Rect]) -> u64 {
+    area_at_least(rects, 2)
+}
+
+/// The spans `top..bottom` crossing a sweep line, each with an index
+/// which is inserted at most once, kept so that finding the ones which
+/// overlap another span costs `O(log n)` plus a step for each one found.
+pub(crate) struct ActiveSpans {
+    ys: Vec<u64>,
+    /// A segment tree over the gaps between consecutive `ys`, like
+    /// [`Coverage`]'s, listing each span at the nodes it covers the whole
+    /// of but not their parents. Removed spans are dropped from a node's
+    /// list the next time it's visited.
+    covering: Vec<Vec<usize>>,
+    /// The top of each span, with its index.
+    tops: BTreeSet<(u64, usize)>,
+    active: Vec<bool>,
+}
+
+impl ActiveSpans {
+    /// No spans yet, for indices below `n` and ends among the compressed
+    /// `ys`.
+    pub(crate) fn new(ys: Vec<u64>, n: usize) -> Self {
+        let nodes = 4 * ys.len().max(2);
+        ActiveSpans {
+            ys,
+            covering: vec![Vec::new(); nodes],
+            tops: BTreeSet::new(),
+            active: vec![false; n],
+        }
+    }
+
+    fn gaps(&self) -> usize {
+        self.ys.len().saturating_sub(1)
+    }
+
+    fn gap(&self, y: u64) -> usize {
+        self.ys.binary_search(&y).expect("y must be compressed")
+    }
+
+    pub(crate) fn insert(&mut self, index: usize, top: u64, bottom: u64) {
+        let (from, to) = (self.gap(top), self.gap(bottom));
+        let gaps = self.gaps();
+        self.list(1, 0, gaps, from, to, index);
+        self.tops.insert((top, index));
+        self.active[index] = true;
+    }
+
+    /// List `index` under `node`, which spans gaps `lo..hi`, for gaps
+    /// `from..to`.
+    fn list(&mut self, node: usize, lo: usize, hi: usize, from: usize, to: usize, index: usize) {
+        if to <= lo || hi <= from {
+            return;
+        }
+        if from <= lo && hi <= to {
+            self.covering[node].push(index);
+        } else {
+            let mid = (lo + hi) / 2;
+            self.list(2 * node, lo, mid, from, to, index);
+            self.list(2 * node + 1, mid, hi, from, to, index);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, index: usize, top: u64) {
+        self.tops.remove(&(top, index));
+        self.active[index] = false;
+    }
+
+    /// Calls `f` with the index of each span with `top <= y < bottom`.
+    pub(crate) fn for_each_containing(&mut self, y: u64, mut f: impl FnMut(usize)) {
+        let gap = self.gap(y);
+        let (mut node, mut lo, mut hi) = (1, 0, self.gaps());
+        if gap >= hi {
+            return;
+        }
+        loop {
+            let active = &self.active;
+            self.covering[node].retain(|&i| active[i]);
+            self.covering[node].iter().for_each(|&i| f(i));
+            if hi - lo == 1 {
+                return;
+            }
+            let mid = (lo + hi) / 2;
+            if gap < mid {
+                node *= 2;
+                hi = mid;
+            } else {
+                node = 2 * node + 1;
+                lo = mid;
+            }
+        }
+    }
+
+    /// Calls `f` with the index of each span sharing part of `top..bottom`,
+    /// which mustn't be empty.
+    pub(crate) fn for_each_overlapping(&mut self, top: u64, bottom: u64, mut f: impl FnMut(usize)) {
+        // The spans which start at or before `top` and reach past it, then
+        // the ones which start after it but before `bottom`.
+        self.for_each_containing(top, &mut f);
+        for &(_, i) in self.tops.range((top + 1, 0)..(bottom, 0)) {
+            f(i);
+        }
+    }
+}
+
+/// Every pair of indices into `spans` which overlap on both axes, in
+/// order.
+///
+/// Each is `[(left, right), (top, bottom)]`, neither of them empty, or
+/// `None` to leave it out. Sweeps across the left edges, finding which of
+/// the spans that haven't ended yet overlap each one vertically with
+/// [`ActiveSpans`].
+pub(crate) fn overlapping_spans(spans: &[Option<[(u64, u64); 2]>]) -> Vec<(usize, usize)> {
+    let present: Vec<(usize, [(u64, u64); 2])> = spans
+        .iter()
+        .enumerate()
+        .filter_map(|(i, span)| span.map(|span| (i, span)))
+        .collect();
+    let mut starts = present.clone();
+    starts.sort_unstable_by_key(|&(_, [(left, _), _])| left);
+    let mut ends = present;
+    ends.sort_unstable_by_key(|&(_, [(_, right), _])| right);
+    let ys = compress(
+        starts
+            .iter()
+            .flat_map(|&(_, [_, (top, bottom)])| [top, bottom]),
+    );
+    let mut active = ActiveSpans::new(ys, spans.len());
+    let mut ends = ends.into_iter().peekable();
+    let mut pairs = Vec::new();
+    for (i, [(left, _), (top, bottom)]) in starts {
+        // Touching edges don't overlap, so these end before `i` starts.
+        while let Some((j, [_, (top, _)])) = ends.next_if(|&(_, [(_, right), _])| right <= left) {
+            active.remove(j, top);
+        }
+        active.for_each_overlapping(top, bottom, |j| pairs.push((i.min(j), i.max(j))));
+        active.insert(i, top, bottom);
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Every pair of indices into `rects` whose rectangles share a square,
+/// in order.
+///
+/// Sweeps across the rectangles' left edges, looking up which of the
+/// ones that haven't ended yet overlap each one vertically in a segment
+/// tree, so it's `O(n log n + k)` for `n` rectangles and `k` pairs.
+pub fn overlapping_pairs(rects: &[Rect]) -> Vec<(usize, usize)> {
+    let spans: Vec<Option<[(u64, u64); 2]>> = rects
+        .iter()
+        .map(|r| (!r.is_empty()).then(|| [(r.left(), r.right()), (r.top(), r.bottom())]))
+        .collect();
+    overlapping_spans(&spans)
+}
+
+/// The indices of `rects` which share no square with any other, in
+/// order.
+pub fn non_overlapping(rects: &[Rect]) -> Vec<usize> {
+    let mut overlaps = vec![false; rects.len()];
+    for (i, j) in overlapping_pairs(rects) {
+        overlaps[i] = true;
+        overlaps[j] = true;
+    }
+    (0..rects.len()).filter(|&i| !overlaps[i]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use aoc_testgen::Lcg;
+
+    use super::{area_at_least, non_overlapping, overlap_area, overlapping_pairs};
+    use crate::Rect;
+
+    #[test]
+    fn test_overlap_area() {
+        let rects = &[
+            Rect::new(1, 3, 4, 4),
+            Rect::new(3, 1, 4, 4),
+            Rect::new(5, 5, 2, 2),
+        ];
+        assert_eq!(overlap_area(rects), 4);
+        // Three deep still only counts once.
+        let rects = &[
+            Rect::new(0, 0, 3, 3),
+            Rect::new(1, 1, 3, 3),
+            Rect::new(1, 1, 1, 1),
+        ];
+        assert_eq!(overlap_area(rects), 4);
+        // Touching edges don't overlap.
+        assert_eq!(
+            overlap_area(&[Rect::new(0, 0, 2, 2), Rect::new(2, 0, 2, 2)]),
+            0
+        );
+        assert_eq!(
+            overlap_area(&[Rect::new(0, 0, 0, 5), Rect::new(0, 0, 5, 5)]),
+            0
+        );
+        assert_eq!(overlap_area(&[]), 0);
+    }
+
+    #[test]
+    fn test_overlap_area_large() {
+        let rects = &[
+            Rect::new(0, 0, 1_000_000, 1_000_000),
+            Rect::new(500_000, 500_000, 1_000_000, 1_000_000),
+            Rect::new(u32::MAX - 1, u32::MAX - 1, u32::MAX, u32::MAX),
+            Rect::new(u32::MAX, u32::MAX, 10, 10),
+        ];
+        assert_eq!(overlap_area(rects), 500_000 * 500_000 + 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "area must fit in a u64")]
+    fn test_area_at_least_overflow() {
+        // Each fits in a u64, but not both together.
+        let max = u32::MAX;
+        area_at_least(&[Rect::new(0, 0, max, max), Rect::new(max, 0, max, max)], 1);
+    }
+
+    #[test]
+    fn test_overlap_area_matches_squares() {
+        let mut rng = Lcg::new(12345);
+        let mut next = move |n: u32| rng.below(n);
+        for _ in 0..50 {
+            let rects: Vec<Rect> = (0..10)
+                .map(|_| Rect::new(next(20), next(20), next(8), next(8)))
+                .collect();
+            let mut grid = [[0; 28]; 28];
+            for rect in &rects {
+                for x in rect.x..rect.x + rect.width {
+                    for y in rect.y..rect.y + rect.height {
+                        grid[x as usize][y as usize] += 1;
+                    }
+                }
+            }
+            for depth in 1..5 {
+                let expected = grid.iter().flatten().filter(|&&n| n >= depth).count() as u64;
+                assert_eq!(
+                    area_at_least(&rects, depth),
+                    expected,
+                    "{} {:?}",
+                    depth,
+                    rects
+                );
+            }
+
+            let mut pairs = Vec::new();
+            for j in 0..rects.len() {
+                for i in 0..j {
+                    if rects[i].intersects(&rects[j]) {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+            pairs.sort_unstable();
+            assert_eq!(overlapping_pairs(&rects), pairs, "{:?}", rects);
+        }
+    }
+
+    #[test]
+    fn test_non_overlapping() {
+        let rects = &[
+            Rect::new(1, 3, 4, 4),
+            Rect::new(3, 1, 4, 4),
+            Rect::new(5, 5, 2, 2),
+            Rect::new(0, 0, 1, 1),
+        ];
+        assert_eq!(overlapping_pairs(rects), vec![(0, 1)]);
+        assert_eq!(non_overlapping(rects), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_overlapping_pairs_stacked() {
+        // All crossing the sweep line at once, but only touching
+        // vertically, apart from the last which spans them all.
+        let mut rects: Vec<Rect> = (0..100).map(|i| Rect::new(i, 3 * i, 200, 3)).collect();
+        assert!(overlapping_pairs(&rects).is_empty());
+        rects.push(Rect::new(150, 1, 1, 298));
+        let pairs: Vec<(usize, usize)> = (0..100).map(|i| (i, 100)).collect();
+        assert_eq!(overlapping_pairs(&rects), pairs);
+        rects.push(Rect::new(150, 0, 1, 0));
+        assert_eq!(overlapping_pairs(&rects), pairs);
+    }
+}
@@ -0,0 +1,208 @@
+//! Checks that claims make sense before solving with them.
+use std::fmt;
+
+use hashbrown::HashMap;
+
+use crate::{Claim, ClaimId, Rect};
+
+/// The fabric claims have to fit on, with its top-left corner at `(0, 0)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Bounds {
+    pub fn new(width: u32, height: u32) -> Self {
+        Bounds { width, height }
+    }
+
+    /// Whether every square of `rect` is on the fabric.
+    pub fn contains(&self, rect: &Rect) -> bool {
+        rect.right() <= u64::from(self.width) && rect.bottom() <= u64::from(self.height)
+    }
+}
+
+impl Default for Bounds {
+    /// The puzzle's fabric, 1000 squares on each side.
+    fn default() -> Self {
+        Bounds::new(1000, 1000)
+    }
+}
+
+/// Something wrong with a claim, on a zero-based `line` of the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// The line isn't a claim in the `#id @ x,y: wxh` format.
+    Malformed { line: usize },
+    /// The claim's ID was already used, on `first_line`.
+    DuplicateId {
+        line: usize,
+        id: ClaimId,
+        first_line: usize,
+    },
+    /// The claim has no width or no height, so covers nothing.
+    ZeroArea { line: usize, id: ClaimId },
+    /// The claim runs off the edge of the fabric.
+    OutOfBounds {
+        line: usize,
+        id: ClaimId,
+        rect: Rect,
+        bounds: Bounds,
+    },
+}
+
+impl Diagnostic {
+    pub fn line(&self) -> usize {
+        match *self {
+            Diagnostic::Malformed { line }
+            | Diagnostic::DuplicateId { line, .. }
+            | Diagnostic::ZeroArea { line, .. }
+            | Diagnostic::OutOfBounds { line, .. } => line,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line() + 1)?;
+        match self {
+            Diagnostic::Malformed { .. } => write!(f, "not a claim"),
+            Diagnostic::DuplicateId { id, first_line, .. } => {
+                write!(f, "{} was already claimed on line {}", id, first_line + 1)
+            }
+            Diagnostic::ZeroArea { id, .. } => write!(f, "{} covers no squares", id),
+            Diagnostic::OutOfBounds {
+                id, rect, bounds, ..
+            } => write!(
+                f,
+                "{} reaches ({}, {}), off the {}x{} fabric",
+                id,
+                rect.right(),
+                rect.bottom(),
+                bounds.width,
+                bounds.height
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Everything wrong with `claims`, where each claim's index is its line,
+/// in line order.
+pub fn check_claims(claims: &[Claim], bounds: Bounds) -> Vec<Diagnostic> {
+    check(claims.iter().enumerate(), bounds)
+}
+
+fn check<'a>(claims: impl Iterator<Item = (usize, &'a Claim)>, bounds: Bounds) -> Vec<Diagnostic> {
+    let mut first_lines: HashMap<ClaimId, usize> = HashMap::new();
+    let mut diagnostics = Vec::new();
+    for (line, claim) in claims {
+        let id = claim.id;
+        if let Some(&first_line) = first_lines.get(&id) {
+            diagnostics.push(Diagnostic::DuplicateId {
+                line,
+                id,
+                first_line,
+            });
+        } else {
+            first_lines.insert(id, line);
+        }
+        if claim.rect.is_empty() {
+            diagnostics.push(Diagnostic::ZeroArea { line, id });
+        } else if !bounds.contains(&claim.rect) {
+            diagnostics.push(Diagnostic::OutOfBounds {
+                line,
+                id,
+                rect: claim.rect,
+                bounds,
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Parses every line of `input` as a claim and checks them, returning the
+/// claims only if nothing is wrong with any of them.
+pub fn parse_claims_checked(input: &[&str], bounds: Bounds) -> Result<Vec<Claim>, Vec<Diagnostic>> {
+    let mut claims = Vec::with_capacity(input.len());
+    let mut malformed = Vec::new();
+    for (line, text) in input.iter().enumerate() {
+        match text.parse::<Claim>() {
+            Ok(claim) => claims.push((line, claim)),
+            Err(()) => malformed.push(Diagnostic::Malformed { line }),
+        }
+    }
+    let mut diagnostics = check(claims.iter().map(|(line, claim)| (*line, claim)), bounds);
+    if malformed.is_empty() && diagnostics.is_empty() {
+        return Ok(claims.into_iter().map(|(_, claim)| claim).collect());
+    }
+    diagnostics.extend(malformed);
+    diagnostics.sort_by_key(Diagnostic::line);
+    Err(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{check_claims, parse_claims_checked, Bounds, Diagnostic};
+    use crate::{parse_claims, ClaimId, Rect};
+
+    #[test]
+    fn test_parse_claims_checked() {
+        let input = &["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"];
+        assert_eq!(
+            parse_claims_checked(input, Bounds::default()),
+            Ok(parse_claims(input))
+        );
+        let diagnostics = parse_claims_checked(input, Bounds::new(7, 6)).unwrap_err();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].line(), 0);
+        assert_eq!(
+            diagnostics[1],
+            Diagnostic::OutOfBounds {
+                line: 2,
+                id: ClaimId(3),
+                rect: Rect::new(5, 5, 2, 2),
+                bounds: Bounds::new(7, 6),
+            }
+        );
+
+        let diagnostics = parse_claims_checked(
+            &[
+                "#1 @ 1,3: 4x4",
+                "#2 @ 3,1 4x4",
+                "#1 @ 5,5: 0x2",
+                "#4 @ 999,0: 2x1",
+            ],
+            Bounds::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "line 2: not a claim",
+                "line 3: #1 was already claimed on line 1",
+                "line 3: #1 covers no squares",
+                "line 4: #4 reaches (1001, 1), off the 1000x1000 fabric",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_claims() {
+        let claims = parse_claims(&["#1 @ 0,0: 1000x1000", "#2 @ 0,1000: 1x0"]);
+        assert_eq!(
+            check_claims(&claims, Bounds::default()),
+            vec![Diagnostic::ZeroArea {
+                line: 1,
+                id: ClaimId(2),
+            }]
+        );
+        assert!(!Bounds::new(1000, 999).contains(&claims[0].rect));
+    }
+}
@@ -0,0 +1,159 @@
+//! What the log says about each guard's sleep.
+use chrono::{prelude::*, Duration, NaiveDateTime};
+
+/// The highest scoring of some candidates, and any others it tied with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Top<T> {
+    /// The lowest of the candidates with the top score.
+    pub winner: T,
+    pub score: u32,
+    /// Every candidate with the top score, including the winner, in order.
+    pub tied: Vec<T>,
+}
+
+impl<T> Top<T> {
+    pub fn is_tie(&self) -> bool {
+        self.tied.len() > 1
+    }
+}
+
+/// The candidates with the top score, or `None` if none of them scored
+/// more than zero.
+pub(crate) fn top<T: Ord + Copy>(candidates: impl IntoIterator<Item = (T, u32)>) -> Option<Top<T>> {
+    let mut score = 0;
+    let mut tied = Vec::new();
+    for (candidate, candidate_score) in candidates {
+        if candidate_score > score {
+            score = candidate_score;
+            tied.clear();
+        }
+        if candidate_score == score && score > 0 {
+            tied.push(candidate);
+        }
+    }
+    tied.sort_unstable();
+    Some(Top {
+        winner: *tied.first()?,
+        score,
+        tied,
+    })
+}
+
+/// One guard's sleep, over every shift they worked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Guard {
+    id: u32,
+    shifts: u32,
+    minutes_asleep: [u32; 60],
+    longest_nap: u32,
+}
+
+impl Guard {
+    pub(crate) fn new(id: u32) -> Self {
+        Guard {
+            id,
+            shifts: 0,
+            minutes_asleep: [0; 60],
+            longest_nap: 0,
+        }
+    }
+
+    pub(crate) fn start_shift(&mut self) {
+        self.shifts += 1;
+    }
+
+    pub(crate) fn add_sleep_window(&mut self, start: &NaiveDateTime, end: &NaiveDateTime) {
+        let total_minutes_sleeping = end.signed_duration_since(*start).num_minutes();
+        for minute in 0..total_minutes_sleeping {
+            let timestamp = start
+                .checked_add_signed(Duration::minutes(minute))
+                .expect("adding timestamps to work");
+            self.minutes_asleep[timestamp.minute() as usize] += 1;
+        }
+        self.longest_nap = self.longest_nap.max(total_minutes_sleeping as u32);
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// How many shifts the guard started.
+    pub fn shifts(&self) -> u32 {
+        self.shifts
+    }
+
+    /// How many times the guard was asleep at each minute past the hour.
+    pub fn minutes_asleep(&self) -> &[u32; 60] {
+        &self.minutes_asleep
+    }
+
+    pub fn total_minutes_sleeping(&self) -> u32 {
+        self.minutes_asleep.iter().sum()
+    }
+
+    pub fn average_minutes_per_shift(&self) -> f64 {
+        match self.shifts {
+            0 => 0.0,
+            shifts => f64::from(self.total_minutes_sleeping()) / f64::from(shifts),
+        }
+    }
+
+    /// The most minutes the guard slept in one go.
+    pub fn longest_nap(&self) -> u32 {
+        self.longest_nap
+    }
+
+    /// The minute the guard was most often asleep, or `None` if they never
+    /// were.
+    pub fn sleepiest_minute(&self) -> Option<Top<u32>> {
+        top((0..).zip(self.minutes_asleep.iter().copied()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::NaiveDateTime;
+
+    use super::{top, Guard, Top};
+
+    #[test]
+    fn test_top() {
+        assert_eq!(
+            top(vec![(3, 5), (1, 2), (2, 5), (4, 0)]),
+            Some(Top {
+                winner: 2,
+                score: 5,
+                tied: vec![2, 3],
+            })
+        );
+        assert_eq!(top(vec![(1, 0), (2, 0)]), None);
+        assert_eq!(top(Vec::<(u32, u32)>::new()), None);
+    }
+
+    #[test]
+    fn test_guard() {
+        let time = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let mut guard = Guard::new(10);
+        assert_eq!(guard.sleepiest_minute(), None);
+        guard.start_shift();
+        guard.add_sleep_window(&time("1518-11-01 00:05"), &time("1518-11-01 00:25"));
+        guard.add_sleep_window(&time("1518-11-01 00:30"), &time("1518-11-01 00:55"));
+        guard.start_shift();
+        guard.add_sleep_window(&time("1518-11-03 00:24"), &time("1518-11-03 00:29"));
+        guard.start_shift();
+        assert_eq!(guard.shifts(), 3);
+        assert_eq!(guard.total_minutes_sleeping(), 50);
+        assert!((guard.average_minutes_per_shift() - 50.0 / 3.0).abs() < 1e-9);
+        assert_eq!(guard.longest_nap(), 25);
+        assert_eq!(guard.minutes_asleep()[24], 2);
+        assert_eq!(
+            guard.sleepiest_minute(),
+            Some(Top {
+                winner: 24,
+                score: 2,
+                tied: vec![24],
+            })
+        );
+    }
+}
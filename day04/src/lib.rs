@@ -1,51 +1,15 @@
 use std::str::FromStr;
 
-use chrono::{prelude::*, Duration, NaiveDateTime};
-use hashbrown::HashMap;
+use aoc_trace::{NoopTracer, Tracer};
+use chrono::NaiveDateTime;
 use lazy_static::lazy_static;
-use log::info;
 use regex::Regex;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
-struct GuardID(u32);
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
-struct Minute(u32);
+mod guard;
+mod shifts;
 
-struct Guard {
-    id: GuardID,
-    minutes_asleep: HashMap<Minute, u32>,
-}
-
-impl Guard {
-    fn new(id: u32) -> Self {
-        Guard {
-            id: GuardID(id),
-            minutes_asleep: HashMap::new(),
-        }
-    }
-    fn add_sleep_window(&mut self, start: &NaiveDateTime, end: &NaiveDateTime) {
-        let total_minutes_sleeping = end.signed_duration_since(*start).num_minutes();
-        for minute in 0..total_minutes_sleeping {
-            let timestamp = start
-                .checked_add_signed(Duration::minutes(minute))
-                .expect("adding timestamps to work");
-            self.minutes_asleep
-                .entry(Minute(timestamp.minute()))
-                .and_modify(|e| *e += 1)
-                .or_insert(1);
-        }
-    }
-    fn most_common(&self) -> Minute {
-        self.minutes_asleep
-            .iter()
-            .max_by_key(|el| el.1)
-            .map(|el| *el.0)
-            .unwrap()
-    }
-    fn total_minutes_sleeping(&self) -> u32 {
-        self.minutes_asleep.values().sum()
-    }
-}
+pub use guard::{Guard, Top};
+pub use shifts::{read_log, read_log_traced, Anomaly, Diagnostic, Mode, Schedule};
 
 enum Action {
     StartsShift(u32),
@@ -53,6 +17,8 @@ enum Action {
     WakesUp,
 }
 
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M";
+
 const LOG_ENTRY_REGEX: &str = r".+Guard #(?P<id>\d+) begins shift";
 
 struct LogEntry {
@@ -68,132 +34,61 @@ impl FromStr for LogEntry {
         }
         let mut s = s.split(']');
         let time = NaiveDateTime::parse_from_str(
-            s.next().unwrap().trim_left_matches('['),
-            "%Y-%m-%d %H:%M",
+            s.next().ok_or(())?.trim_start_matches('['),
+            TIMESTAMP_FORMAT,
         )
-        .expect("failed to parse timestamp");
-        let action = match s.next().unwrap() {
+        .map_err(|_| ())?;
+        let action = match s.next().ok_or(())? {
             " wakes up" => Action::WakesUp,
             " falls asleep" => Action::FallsAsleep,
             other => Action::StartsShift(
-                RE.captures(other).expect("no regex match found")["id"]
+                RE.captures(other).ok_or(())?["id"]
                     .parse()
-                    .expect("failed to parse guard ID"),
+                    .map_err(|_| ())?,
             ),
         };
         Ok(LogEntry { time, action })
     }
 }
 
-fn build_guard_map(entries: &[LogEntry]) -> HashMap<GuardID, Guard> {
-    let mut guards: HashMap<GuardID, Guard> = HashMap::new();
-    let mut iter = entries.iter();
-    info!("Looking for guard actions");
-    loop {
-        let entry = iter.next();
-
-        if entry.is_none() {
-            // We're out of log entries
-            break;
-        };
-
-        match &entry.unwrap() {
-            LogEntry {
-                time,
-                action: Action::StartsShift(guard_id),
-            } => {
-                info!("Guard {} started shift at {}", guard_id, time);
-                // Assume we'll always see a 'falls asleep' before 'wakes up'.
-                // Otherwise we'll panic.
-                let mut start_time = None;
-                let mut guard_iter = iter.clone();
-                loop {
-                    match guard_iter.next() {
-                        Some(LogEntry {
-                            time: start,
-                            action: Action::FallsAsleep,
-                        }) => {
-                            info!("Guard {} fell asleep at {}", guard_id, start);
-                            start_time = Some(start);
-                        }
-                        Some(LogEntry {
-                            time: end,
-                            action: Action::WakesUp,
-                        }) => {
-                            info!("Guard {} woke up at {}", guard_id, end);
-                            guards
-                                .entry(GuardID(*guard_id))
-                                .and_modify(|guard| {
-                                    guard.add_sleep_window(
-                                        start_time.expect("Woke up before falling asleep"),
-                                        end,
-                                    )
-                                })
-                                .or_insert({
-                                    let mut guard = Guard::new(*guard_id);
-                                    guard.add_sleep_window(
-                                        start_time.expect("Woke up before falling asleep"),
-                                        end,
-                                    );
-                                    guard
-                                });
-                        }
-                        _ => {
-                            info!("Moving to next guard");
-                            break;
-                        }
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-    guards
+pub fn part1(input: &[&str]) -> u32 {
+    part1_traced(input, &mut NoopTracer)
 }
 
-pub fn part1(input: &[&str]) -> u32 {
-    info!("Parsing log entries");
-    let mut entries: Vec<LogEntry> = input
-        .iter()
-        .map(|x| LogEntry::from_str(x).expect("could not parse log entry"))
-        .collect();
-    entries.sort_unstable_by_key(|el| el.time);
-    let guards = build_guard_map(&entries);
-    let sleepiest_guard: GuardID = guards
-        .values()
-        .map(|guard| (guard.id, guard.total_minutes_sleeping()))
-        .max_by_key(|el| el.1)
-        .map(|el| el.0)
-        .unwrap();
-    let most_common = guards[&sleepiest_guard].most_common();
-    most_common.0 * sleepiest_guard.0
+/// As `part1`, but recording shift, sleep and wake events into `tracer`.
+pub fn part1_traced(input: &[&str], tracer: &mut impl Tracer) -> u32 {
+    let schedule =
+        read_log_traced(input, Mode::Lenient, tracer).expect("lenient reading always succeeds");
+    let sleepiest_guard = schedule
+        .sleepiest_guard()
+        .expect("some guard must fall asleep");
+    let most_common = schedule.guards()[&sleepiest_guard.winner]
+        .sleepiest_minute()
+        .expect("the sleepiest guard must fall asleep");
+    most_common.winner * sleepiest_guard.winner
 }
 
 pub fn part2(input: &[&str]) -> u32 {
-    info!("Parsing log entries");
-    let mut entries: Vec<LogEntry> = input
-        .iter()
-        .map(|x| LogEntry::from_str(x).expect("could not parse log entry"))
-        .collect();
-    entries.sort_unstable_by_key(|el| el.time);
-    let guards = build_guard_map(&entries);
-    let sleepiest_guard: (GuardID, Minute, u32) = guards
-        .into_iter()
-        .flat_map(|(guard_id, guard)| {
-            guard
-                .minutes_asleep
-                .into_iter()
-                .map(move |(k, v)| (guard_id, k, v))
-        })
-        .max_by_key(|el| el.2)
-        .unwrap();
-    (sleepiest_guard.0).0 * (sleepiest_guard.1).0
+    part2_traced(input, &mut NoopTracer)
+}
+
+/// As `part2`, but recording shift, sleep and wake events into `tracer`.
+pub fn part2_traced(input: &[&str], tracer: &mut impl Tracer) -> u32 {
+    let schedule =
+        read_log_traced(input, Mode::Lenient, tracer).expect("lenient reading always succeeds");
+    let (guard, minute) = schedule
+        .most_regular_sleep()
+        .expect("some guard must fall asleep")
+        .winner;
+    guard * minute
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::{part1, part2};
+    use aoc_trace::{Event, Trace};
+
+    use super::{part1, part1_traced, part2, read_log, Anomaly, Diagnostic, Mode, Top};
 
     #[test]
     fn test_part1() {
@@ -242,4 +137,215 @@ mod tests {
         ];
         assert_eq!(part2(input), 4455);
     }
+
+    #[test]
+    fn test_part1_traced() {
+        let input = &[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:25] wakes up",
+            "[1518-11-01 23:58] Guard #99 begins shift",
+            "[1518-11-02 00:40] falls asleep",
+            "[1518-11-02 00:50] wakes up",
+            "[1518-11-03 00:05] Guard #10 begins shift",
+            "[1518-11-03 00:24] falls asleep",
+            "[1518-11-03 00:29] wakes up",
+        ];
+        let mut trace = Trace::new();
+        assert_eq!(part1_traced(input, &mut trace), 240);
+        assert_eq!(
+            trace.events(),
+            &[
+                Event::ShiftStarted {
+                    guard: 10,
+                    time: "1518-11-01 00:00".to_string()
+                },
+                Event::GuardFellAsleep {
+                    guard: 10,
+                    time: "1518-11-01 00:05".to_string()
+                },
+                Event::GuardWokeUp {
+                    guard: 10,
+                    time: "1518-11-01 00:25".to_string()
+                },
+                Event::ShiftStarted {
+                    guard: 99,
+                    time: "1518-11-01 23:58".to_string()
+                },
+                Event::GuardFellAsleep {
+                    guard: 99,
+                    time: "1518-11-02 00:40".to_string()
+                },
+                Event::GuardWokeUp {
+                    guard: 99,
+                    time: "1518-11-02 00:50".to_string()
+                },
+                Event::ShiftStarted {
+                    guard: 10,
+                    time: "1518-11-03 00:05".to_string()
+                },
+                Event::GuardFellAsleep {
+                    guard: 10,
+                    time: "1518-11-03 00:24".to_string()
+                },
+                Event::GuardWokeUp {
+                    guard: 10,
+                    time: "1518-11-03 00:29".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_log_anomalies() {
+        let input = &[
+            "[1518-10-31 23:50] falls asleep",
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:07] falls asleep",
+            "[1518-11-01 00:25] wakes up",
+            "[1518-11-01 00:26] wakes up",
+            "[1518-11-01 00:30] falls asleep",
+            "[1518-11-01 23:58] Guard #99 begins shift",
+            "[1518-11-02 00:40] dozes off",
+            "[1518-11-02 00:45] falls asleep",
+        ];
+        let schedule = read_log(input, Mode::Lenient).unwrap();
+        let anomalies: Vec<(usize, &Anomaly)> = schedule
+            .diagnostics()
+            .iter()
+            .map(|d| (d.line, &d.anomaly))
+            .collect();
+        assert_eq!(
+            anomalies,
+            vec![
+                (0, &Anomaly::BeforeFirstShift),
+                (3, &Anomaly::DoubleSleep { guard: 10 }),
+                (5, &Anomaly::WakeWithoutSleep { guard: 10 }),
+                (6, &Anomaly::UnterminatedSleep { guard: 10 }),
+                (8, &Anomaly::Malformed),
+                (9, &Anomaly::UnterminatedSleep { guard: 99 }),
+            ]
+        );
+        // The first sleep counts, from 00:05 rather than 00:07.
+        assert_eq!(schedule.guards()[&10].total_minutes_sleeping(), 20);
+        assert_eq!(schedule.guards()[&99].total_minutes_sleeping(), 0);
+
+        let diagnostics = read_log(input, Mode::Strict).unwrap_err();
+        assert_eq!(diagnostics, schedule.diagnostics());
+        assert_eq!(
+            diagnostics[2].to_string(),
+            "line 6: guard #10 woke up without falling asleep: \"[1518-11-01 00:26] wakes up\""
+        );
+    }
+
+    #[test]
+    fn test_read_log_before_first_shift() {
+        let input = &[
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:25] wakes up",
+            "[1518-11-01 00:30] Guard #10 begins shift",
+        ];
+        assert_eq!(
+            read_log(input, Mode::Strict).err(),
+            Some(vec![
+                Diagnostic {
+                    line: 0,
+                    entry: input[0].to_string(),
+                    anomaly: Anomaly::BeforeFirstShift,
+                },
+                Diagnostic {
+                    line: 1,
+                    entry: input[1].to_string(),
+                    anomaly: Anomaly::BeforeFirstShift,
+                },
+            ])
+        );
+        assert!(read_log(&input[2..], Mode::Strict)
+            .unwrap()
+            .diagnostics()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_schedule_stats() {
+        let input = &[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:25] wakes up",
+            "[1518-11-01 00:30] falls asleep",
+            "[1518-11-01 00:55] wakes up",
+            "[1518-11-01 23:58] Guard #99 begins shift",
+            "[1518-11-02 00:40] falls asleep",
+            "[1518-11-02 00:50] wakes up",
+            "[1518-11-03 00:05] Guard #10 begins shift",
+            "[1518-11-03 00:24] falls asleep",
+            "[1518-11-03 00:29] wakes up",
+            "[1518-11-04 00:02] Guard #99 begins shift",
+            "[1518-11-04 00:36] falls asleep",
+            "[1518-11-04 00:46] wakes up",
+            "[1518-11-05 00:03] Guard #99 begins shift",
+            "[1518-11-05 00:45] falls asleep",
+            "[1518-11-05 00:55] wakes up",
+        ];
+        let schedule = read_log(input, Mode::Strict).unwrap();
+        let stats: Vec<(u32, u32, u32, u32)> = schedule
+            .guards()
+            .values()
+            .map(|g| {
+                (
+                    g.id(),
+                    g.shifts(),
+                    g.total_minutes_sleeping(),
+                    g.longest_nap(),
+                )
+            })
+            .collect();
+        assert_eq!(stats, vec![(10, 2, 50, 25), (99, 3, 30, 10)]);
+        assert!((schedule.guards()[&99].average_minutes_per_shift() - 10.0).abs() < 1e-9);
+        assert_eq!(schedule.sleepiest_guard().map(|top| top.winner), Some(10));
+        assert_eq!(
+            schedule.sleepiest_at(45),
+            Some(Top {
+                winner: 99,
+                score: 3,
+                tied: vec![99],
+            })
+        );
+        assert_eq!(schedule.sleepiest_at(2), None);
+        assert_eq!(schedule.sleepiest_at(60), None);
+        assert_eq!(
+            schedule.most_regular_sleep(),
+            Some(Top {
+                winner: (99, 45),
+                score: 3,
+                tied: vec![(99, 45)],
+            })
+        );
+    }
+
+    #[test]
+    fn test_ties() {
+        let input = &[
+            "[1518-11-01 00:00] Guard #99 begins shift",
+            "[1518-11-01 00:10] falls asleep",
+            "[1518-11-01 00:12] wakes up",
+            "[1518-11-02 00:00] Guard #10 begins shift",
+            "[1518-11-02 00:10] falls asleep",
+            "[1518-11-02 00:11] wakes up",
+            "[1518-11-02 00:20] falls asleep",
+            "[1518-11-02 00:21] wakes up",
+        ];
+        let schedule = read_log(input, Mode::Strict).unwrap();
+        // Ties go to the lowest guard, then the earliest minute.
+        let sleepiest = schedule.sleepiest_guard().unwrap();
+        assert_eq!((sleepiest.winner, sleepiest.tied), (10, vec![10, 99]));
+        assert!(schedule.sleepiest_at(10).unwrap().is_tie());
+        assert_eq!(
+            schedule.most_regular_sleep().unwrap().tied,
+            vec![(10, 10), (10, 20), (99, 10), (99, 11)]
+        );
+        assert_eq!(part1(input), 100);
+        assert_eq!(part2(input), 100);
+    }
 }
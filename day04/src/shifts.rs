@@ -0,0 +1,245 @@
+//! Reading the log as a sequence of shifts, checking each guard falls
+//! asleep and wakes up in turn.
+use std::collections::BTreeMap;
+use std::fmt;
+
+use aoc_trace::{Event, Tracer};
+use chrono::NaiveDateTime;
+use log::{info, warn};
+
+use crate::guard::top;
+use crate::{Action, Guard, LogEntry, Top, TIMESTAMP_FORMAT};
+
+/// What to do when the log doesn't make sense.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Give up, returning every anomaly found.
+    Strict,
+    /// Skip past each anomaly, reporting it alongside the guards' sleep.
+    Lenient,
+}
+
+/// Something in the log which doesn't fit the pattern of a guard starting
+/// a shift, then falling asleep and waking up in turn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Anomaly {
+    /// The entry isn't a timestamp followed by a known action.
+    Malformed,
+    /// The guard woke up without having fallen asleep. Leniently, the
+    /// entry is ignored.
+    WakeWithoutSleep { guard: u32 },
+    /// The guard fell asleep while already asleep. Leniently, they're
+    /// taken to have been asleep since the first time.
+    DoubleSleep { guard: u32 },
+    /// The guard fell asleep here but never woke up before the next shift
+    /// or the end of the log. Leniently, the sleep isn't counted.
+    UnterminatedSleep { guard: u32 },
+    /// Someone fell asleep or woke up before any guard's shift had
+    /// started. Leniently, the entry is ignored.
+    BeforeFirstShift,
+}
+
+/// An anomaly, with the (zero-based) line of the input it was found on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub entry: String,
+    pub anomaly: Anomaly,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line + 1)?;
+        match self.anomaly {
+            Anomaly::Malformed => write!(f, "not a log entry")?,
+            Anomaly::WakeWithoutSleep { guard } => {
+                write!(f, "guard #{} woke up without falling asleep", guard)?
+            }
+            Anomaly::DoubleSleep { guard } => {
+                write!(f, "guard #{} fell asleep while already asleep", guard)?
+            }
+            Anomaly::UnterminatedSleep { guard } => {
+                write!(f, "guard #{} fell asleep and never woke up", guard)?
+            }
+            Anomaly::BeforeFirstShift => write!(f, "no guard's shift has started yet")?,
+        }
+        write!(f, ": {:?}", self.entry)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// The guards' sleep, as read from the log.
+#[derive(Debug)]
+pub struct Schedule {
+    guards: BTreeMap<u32, Guard>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Schedule {
+    /// The anomalies skipped past while reading the log leniently, in the
+    /// order of the lines they're on.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Every guard who started a shift, by ID.
+    pub fn guards(&self) -> &BTreeMap<u32, Guard> {
+        &self.guards
+    }
+
+    /// The guard who slept the most minutes overall (part 1's first
+    /// strategy), or `None` if nobody slept.
+    pub fn sleepiest_guard(&self) -> Option<Top<u32>> {
+        top(self
+            .guards
+            .values()
+            .map(|guard| (guard.id(), guard.total_minutes_sleeping())))
+    }
+
+    /// The guard who was most often asleep at `minute` past the hour, or
+    /// `None` if nobody ever was.
+    pub fn sleepiest_at(&self, minute: u32) -> Option<Top<u32>> {
+        top(self.guards.values().map(|guard| {
+            let asleep = guard.minutes_asleep().get(minute as usize);
+            (guard.id(), asleep.copied().unwrap_or(0))
+        }))
+    }
+
+    /// The guard and minute past the hour at which they were asleep most
+    /// often (part 2's strategy), or `None` if nobody slept.
+    pub fn most_regular_sleep(&self) -> Option<Top<(u32, u32)>> {
+        top(self.guards.values().flat_map(|guard| {
+            (0..)
+                .zip(guard.minutes_asleep().iter())
+                .map(move |(minute, &times)| ((guard.id(), minute), times))
+        }))
+    }
+}
+
+enum State<'a> {
+    BeforeFirstShift,
+    /// On shift, and maybe asleep since an entry.
+    OnShift {
+        guard: u32,
+        asleep: Option<(usize, &'a NaiveDateTime)>,
+    },
+}
+
+/// Reads the log in `input`, which can be in any order.
+pub fn read_log(input: &[&str], mode: Mode) -> Result<Schedule, Vec<Diagnostic>> {
+    read_log_traced(input, mode, &mut aoc_trace::NoopTracer)
+}
+
+/// As `read_log`, but recording shift, sleep and wake events into `tracer`.
+pub fn read_log_traced(
+    input: &[&str],
+    mode: Mode,
+    tracer: &mut impl Tracer,
+) -> Result<Schedule, Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let diagnostic = |line: usize, anomaly| Diagnostic {
+        line,
+        entry: input[line].to_string(),
+        anomaly,
+    };
+
+    info!("Parsing log entries");
+    let mut entries: Vec<(usize, LogEntry)> = Vec::with_capacity(input.len());
+    for (line, text) in input.iter().enumerate() {
+        match text.parse() {
+            Ok(entry) => entries.push((line, entry)),
+            Err(()) => diagnostics.push(diagnostic(line, Anomaly::Malformed)),
+        }
+    }
+    // Stable, so entries at the same time stay in the order they were
+    // written.
+    entries.sort_by_key(|(_, entry)| entry.time);
+
+    info!("Looking for guard actions");
+    let mut guards: BTreeMap<u32, Guard> = BTreeMap::new();
+    let mut state = State::BeforeFirstShift;
+    for (line, LogEntry { time, action }) in &entries {
+        let line = *line;
+        let timestamp = || time.format(TIMESTAMP_FORMAT).to_string();
+        match (&mut state, action) {
+            (State::BeforeFirstShift, Action::FallsAsleep)
+            | (State::BeforeFirstShift, Action::WakesUp) => {
+                diagnostics.push(diagnostic(line, Anomaly::BeforeFirstShift));
+            }
+            (state, Action::StartsShift(guard_id)) => {
+                if let State::OnShift {
+                    guard,
+                    asleep: Some((start_line, _)),
+                } = *state
+                {
+                    diagnostics.push(diagnostic(start_line, Anomaly::UnterminatedSleep { guard }));
+                }
+                info!("Guard {} started shift at {}", guard_id, time);
+                tracer.record(Event::ShiftStarted {
+                    guard: *guard_id,
+                    time: timestamp(),
+                });
+                guards
+                    .entry(*guard_id)
+                    .or_insert_with(|| Guard::new(*guard_id))
+                    .start_shift();
+                *state = State::OnShift {
+                    guard: *guard_id,
+                    asleep: None,
+                };
+            }
+            (State::OnShift { guard, asleep }, Action::FallsAsleep) => match asleep {
+                Some(_) => {
+                    diagnostics.push(diagnostic(line, Anomaly::DoubleSleep { guard: *guard }));
+                }
+                None => {
+                    info!("Guard {} fell asleep at {}", guard, time);
+                    tracer.record(Event::GuardFellAsleep {
+                        guard: *guard,
+                        time: timestamp(),
+                    });
+                    *asleep = Some((line, time));
+                }
+            },
+            (State::OnShift { guard, asleep }, Action::WakesUp) => match asleep.take() {
+                None => {
+                    diagnostics.push(diagnostic(
+                        line,
+                        Anomaly::WakeWithoutSleep { guard: *guard },
+                    ));
+                }
+                Some((_, start)) => {
+                    info!("Guard {} woke up at {}", guard, time);
+                    tracer.record(Event::GuardWokeUp {
+                        guard: *guard,
+                        time: timestamp(),
+                    });
+                    guards
+                        .get_mut(guard)
+                        .expect("guards are added when their shift starts")
+                        .add_sleep_window(start, time);
+                }
+            },
+        }
+    }
+    if let State::OnShift {
+        guard,
+        asleep: Some((start_line, _)),
+    } = state
+    {
+        diagnostics.push(diagnostic(start_line, Anomaly::UnterminatedSleep { guard }));
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+    for diagnostic in &diagnostics {
+        warn!("{}", diagnostic);
+    }
+    match mode {
+        Mode::Strict if !diagnostics.is_empty() => Err(diagnostics),
+        _ => Ok(Schedule {
+            guards,
+            diagnostics,
+        }),
+    }
+}
@@ -1,3 +1,11 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+
+use aoc_trace::{Event, NoopTracer, Tracer};
+
 #[inline(always)]
 fn compare_elements(x: char, y: char) -> bool {
     // My version
@@ -8,11 +16,22 @@ fn compare_elements(x: char, y: char) -> bool {
 }
 
 pub fn part1(input: &str) -> usize {
+    part1_traced(input, &mut NoopTracer)
+}
+
+/// As `part1`, but recording each pair of units which react into `tracer`.
+pub fn part1_traced(input: &str, tracer: &mut impl Tracer) -> usize {
     input
         .chars()
-        .fold(Vec::new(), |mut acc, x| {
+        .enumerate()
+        .fold(Vec::new(), |mut acc, (index, x)| {
             match acc.last() {
                 Some(prev) if compare_elements(x, *prev) => {
+                    tracer.record(Event::UnitsReacted {
+                        index,
+                        left: *prev,
+                        right: x,
+                    });
                     acc.pop();
                 }
                 _ => {
@@ -44,7 +63,8 @@ pub fn part2(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
 
-    use super::{part1, part2};
+    use super::{part1, part1_traced, part2};
+    use aoc_trace::{Event, Trace};
     use env_logger;
 
     #[test]
@@ -61,6 +81,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_part1_traced() {
+        let mut trace = Trace::new();
+        assert_eq!(part1_traced("abBA", &mut trace), 0);
+        assert_eq!(
+            trace.events(),
+            &[
+                Event::UnitsReacted {
+                    index: 2,
+                    left: 'b',
+                    right: 'B'
+                },
+                Event::UnitsReacted {
+                    index: 3,
+                    left: 'a',
+                    right: 'A'
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_part2() {
         assert_eq!(part2("dabAcCaCBAcCcaDA"), 4);
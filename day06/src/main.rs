@@ -1,4 +1,4 @@
-use std::time;
+use std::time::Instant;
 
 use day06;
 
@@ -7,11 +7,8 @@ fn main() {
         .default_format_timestamp_nanos(true)
         .init();
     let input: Vec<&str> = include_str!("../../input/2018/day6.txt").lines().collect();
-    let start = time::SystemTime::now();
+    let start = Instant::now();
     println!("Part 1: {}", day06::part1(&input));
     println!("Part 2: {}", day06::part2(&input, 10000));
-    println!(
-        "Ran in {:?}",
-        time::SystemTime::now().duration_since(start).unwrap()
-    );
+    println!("Ran in {:?}", start.elapsed());
 }
@@ -1,5 +1,15 @@
-use std::collections::{BTreeSet, HashMap};
-use std::str::FromStr;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use aoc_trace::{Event, NoopTracer, Tracer};
+use hashbrown::HashMap;
 
 type Task = char;
 
@@ -55,7 +65,7 @@ fn process_graph(
         done_v.push(next);
         let node = &graph[&next];
         for potential in &node.blocks {
-            if graph[&potential].blocked_by.is_subset(&done) {
+            if graph[potential].blocked_by.is_subset(&done) {
                 available.insert(*potential);
             }
         }
@@ -78,6 +88,16 @@ pub fn part1(input: &[&str]) -> String {
 }
 
 pub fn part2(input: &[&str], n_workers: usize, base_duration: usize) -> String {
+    part2_traced(input, n_workers, base_duration, &mut NoopTracer)
+}
+
+/// As `part2`, but recording each task start and finish into `tracer`.
+pub fn part2_traced(
+    input: &[&str],
+    n_workers: usize,
+    base_duration: usize,
+    tracer: &mut impl Tracer,
+) -> String {
     let edges: Vec<Edge> = input.iter().map(|l| Edge::from_str(l).unwrap()).collect();
     let graph = new_graph(&edges);
 
@@ -106,17 +126,22 @@ pub fn part2(input: &[&str], n_workers: usize, base_duration: usize) -> String {
                 total_time += fastest_task.1;
 
                 // Now check if any of the workers have finished their tasks.
-                for mut worker in &mut workers {
+                for (i, mut worker) in workers.iter_mut().enumerate() {
                     match &mut worker {
                         // If so, remove the task from them, add the task to
                         // the 'done' tasks, and insert any newly available
                         // tasks.
                         Some(current_task) if current_task.1 == fastest_task.1 => {
+                            tracer.record(Event::TaskFinished {
+                                worker: i,
+                                task: current_task.0,
+                                time: total_time,
+                            });
                             done.insert(current_task.0);
 
                             let task = &graph[&current_task.0];
                             for potential in &task.blocks {
-                                if graph[&potential].blocked_by.is_subset(&done) {
+                                if graph[potential].blocked_by.is_subset(&done) {
                                     available.insert(*potential);
                                 }
                             }
@@ -138,12 +163,17 @@ pub fn part2(input: &[&str], n_workers: usize, base_duration: usize) -> String {
         // Now that we've determined the available tasks, assign the idle
         // workers the available tasks in order, removing that task
         // from the 'available' set.
-        for worker in &mut workers {
+        for (i, worker) in workers.iter_mut().enumerate() {
             if worker.is_none() {
                 let next = available.iter().next().cloned();
                 match next {
                     Some(t) => {
                         available.remove(&t);
+                        tracer.record(Event::TaskStarted {
+                            worker: i,
+                            task: t,
+                            time: total_time,
+                        });
                         *worker = Some((t, base_duration + (t as u8 - 64) as usize));
                     }
                     None => {}
@@ -157,7 +187,9 @@ pub fn part2(input: &[&str], n_workers: usize, base_duration: usize) -> String {
 #[cfg(test)]
 mod tests {
 
-    use super::{part1, part2};
+    use aoc_trace::{Event, Trace};
+
+    use super::{part1, part2, part2_traced};
 
     #[test]
     fn test_part1() {
@@ -186,4 +218,37 @@ mod tests {
         ];
         assert_eq!(part2(input, 2, 0), "15");
     }
+
+    #[test]
+    fn test_part2_traced() {
+        let input = &[
+            "Step C must be finished before step A can begin.",
+            "Step C must be finished before step F can begin.",
+            "Step A must be finished before step B can begin.",
+            "Step A must be finished before step D can begin.",
+            "Step B must be finished before step E can begin.",
+            "Step D must be finished before step E can begin.",
+            "Step F must be finished before step E can begin.",
+        ];
+        let mut trace = Trace::new();
+        assert_eq!(part2_traced(input, 2, 0, &mut trace), "15");
+        let events = trace.events();
+        assert_eq!(events.len(), 12);
+        assert_eq!(
+            events[0],
+            Event::TaskStarted {
+                worker: 0,
+                task: 'C',
+                time: 0
+            }
+        );
+        assert_eq!(
+            events[11],
+            Event::TaskFinished {
+                worker: 0,
+                task: 'E',
+                time: 15
+            }
+        );
+    }
 }
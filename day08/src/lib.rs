@@ -1,3 +1,9 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
 #[derive(Default, Debug)]
 struct Node {
     children: Vec<Node>,
@@ -1,8 +1,8 @@
 use std::ops::RangeInclusive;
 
 use itertools::Itertools;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use summed_area_table::{SummedAreaTable, SummedAreaTableSource, VecSource};
 
 #[derive(Clone, Copy, Debug)]
 struct Coordinates {
@@ -26,33 +26,62 @@ type Axis = RangeInclusive<usize>;
 
 fn powers(x_range: Axis, y_range: Axis, serial: Serial) -> Vec<isize> {
     let all_coords: Vec<(usize, usize)> = x_range.cartesian_product(y_range).collect();
+    #[cfg(feature = "parallel")]
+    let all_coords = all_coords.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let all_coords = all_coords.into_iter();
     all_coords
-        .into_par_iter()
         .map(|(x, y)| power_level(Coordinates { x, y }, serial))
         .collect()
 }
 
-fn max_corner(
-    x_range: &[usize],
-    y_range: &[usize],
-    summed: &SummedAreaTable,
-    window: usize,
-) -> (Coordinates, isize) {
-    x_range
-        .windows(window)
-        .map(|xs| {
-            y_range
-                .windows(window)
-                .map(|ys| {
-                    // Subtract one because index in x_range / y_range start at 1
-                    let top_left = (xs[0] - 1, ys[0] - 1);
-                    let bottom_right = (xs[window - 1] - 1, ys[window - 1] - 1);
-                    let sum = summed.get_sum(top_left, bottom_right);
-                    (Coordinates { x: xs[0], y: ys[0] }, sum as isize)
-                })
-                .collect::<Vec<(Coordinates, isize)>>()
+/// Running totals over a square grid, so the sum of any square can be
+/// found with four lookups.
+///
+/// `sums[i * (width + 1) + j]` holds the total of every cell with
+/// `x <= i` and `y <= j`; row and column zero are left as zero padding so
+/// the one-indexed puzzle coordinates can be used directly.
+struct SummedAreaTable {
+    width: usize,
+    sums: Vec<isize>,
+}
+
+impl SummedAreaTable {
+    /// Build the table from values laid out as produced by `powers`, i.e.
+    /// `values[(x - 1) * width + (y - 1)]`.
+    fn new(values: &[isize], width: usize) -> Self {
+        let stride = width + 1;
+        let mut sums = vec![0; stride * stride];
+        for x in 1..=width {
+            for y in 1..=width {
+                sums[x * stride + y] = values[(x - 1) * width + (y - 1)]
+                    + sums[(x - 1) * stride + y]
+                    + sums[x * stride + y - 1]
+                    - sums[(x - 1) * stride + y - 1];
+            }
+        }
+        SummedAreaTable { width, sums }
+    }
+
+    /// The total of the `size` x `size` square with top-left corner `coords`.
+    fn square_sum(&self, coords: Coordinates, size: usize) -> isize {
+        let stride = self.width + 1;
+        let (x0, y0) = (coords.x - 1, coords.y - 1);
+        let (x1, y1) = (x0 + size, y0 + size);
+        self.sums[x1 * stride + y1] - self.sums[x0 * stride + y1] - self.sums[x1 * stride + y0]
+            + self.sums[x0 * stride + y0]
+    }
+}
+
+fn max_corner(summed: &SummedAreaTable, window: usize) -> (Coordinates, isize) {
+    let corners = 1..=(summed.width - window + 1);
+    corners
+        .clone()
+        .cartesian_product(corners)
+        .map(|(x, y)| {
+            let coords = Coordinates { x, y };
+            (coords, summed.square_sum(coords, window))
         })
-        .flatten()
         .max_by_key(|el| el.1)
         .unwrap()
 }
@@ -61,28 +90,27 @@ pub fn part1(serial: usize, width: usize, window: usize) -> (usize, usize) {
     let serial = Serial(serial);
     let x = 1..=width;
     let y = x.clone();
-    let powers = powers(x.clone(), y.clone(), serial);
-    let summed = VecSource::new(&powers, width, width).calculate_full_summed_area_table();
-    let x: Vec<usize> = x.collect();
-    let y: Vec<usize> = y.collect();
-    let coordinates = max_corner(&x, &y, &summed, window).0;
-    (coordinates.y, coordinates.x)
+    let powers = powers(x, y, serial);
+    let summed = SummedAreaTable::new(&powers, width);
+    let coordinates = max_corner(&summed, window).0;
+    (coordinates.x, coordinates.y)
 }
 
 pub fn part2(serial: usize, width: usize) -> (usize, usize, usize) {
     let serial = Serial(serial);
     let x = 1..=width;
     let y = x.clone();
-    let powers = powers(x.clone(), y.clone(), serial);
-    let summed = VecSource::new(&powers, width, width).calculate_full_summed_area_table();
-    let x: Vec<usize> = x.collect();
-    let y: Vec<usize> = y.collect();
-    let (coordinates, window) = (1..(width + 1))
-        .into_par_iter()
-        .map(|window| (max_corner(&x, &y, &summed, window), window))
+    let powers = powers(x, y, serial);
+    let summed = SummedAreaTable::new(&powers, width);
+    #[cfg(feature = "parallel")]
+    let windows = (1..=width).into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let windows = 1..=width;
+    let (coordinates, window) = windows
+        .map(|window| (max_corner(&summed, window), window))
         .max_by_key(|el| (el.0).1)
         .unwrap();
-    (coordinates.0.y, coordinates.0.x, window)
+    (coordinates.0.x, coordinates.0.y, window)
 }
 
 #[cfg(test)]